@@ -0,0 +1,256 @@
+use std::{fmt, mem};
+
+use bevy::{ecs::system::SystemParam, input::InputSystem, prelude::*, utils::HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+
+//what the player can do, whatever device they do it with
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Flap,
+    Pause,
+    Restart,
+    //menu confirm, starts a run from the menus
+    Confirm,
+    //menu back, leaves a menu
+    Back,
+}
+
+impl Action {
+    pub const ALL: [Action; 5] = [
+        Action::Flap,
+        Action::Pause,
+        Action::Restart,
+        Action::Confirm,
+        Action::Back,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Flap => "flap",
+            Action::Pause => "pause",
+            Action::Restart => "restart",
+            Action::Confirm => "confirm",
+            Action::Back => "back",
+        }
+    }
+}
+
+//one input which triggers an action
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    //any connected gamepad
+    Gamepad(GamepadButton),
+    //a finger landing anywhere on the screen
+    Touch,
+}
+
+impl Binding {
+    //a new binding only replaces the ones of the same kind, so rebinding a key keeps the gamepad working
+    fn same_device(&self, other: &Binding) -> bool {
+        mem::discriminant(self) == mem::discriminant(other)
+    }
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Binding::Key(KeyCode::Escape) => write!(f, "esc"),
+            Binding::Key(key) => {
+                let name = format!("{key:?}");
+                let name = name
+                    .strip_prefix("Key")
+                    .or_else(|| name.strip_prefix("Digit"))
+                    .unwrap_or(&name);
+                write!(f, "{}", name.to_lowercase())
+            }
+            Binding::Mouse(button) => write!(f, "mouse {}", format!("{button:?}").to_lowercase()),
+            Binding::Gamepad(button) => write!(f, "pad {}", format!("{button:?}").to_lowercase()),
+            Binding::Touch => write!(f, "tap"),
+        }
+    }
+}
+
+//what every action is bound to, kept in the settings file
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Bindings {
+    pub flap: Vec<Binding>,
+    pub pause: Vec<Binding>,
+    pub restart: Vec<Binding>,
+    pub confirm: Vec<Binding>,
+    pub back: Vec<Binding>,
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings {
+            flap: vec![
+                Binding::Key(KeyCode::Space),
+                Binding::Mouse(MouseButton::Left),
+                Binding::Gamepad(GamepadButton::South),
+                Binding::Touch,
+            ],
+            pause: vec![
+                Binding::Key(KeyCode::Escape),
+                Binding::Gamepad(GamepadButton::Start),
+            ],
+            restart: vec![
+                Binding::Key(KeyCode::KeyR),
+                Binding::Gamepad(GamepadButton::Select),
+            ],
+            confirm: vec![
+                Binding::Key(KeyCode::Space),
+                Binding::Key(KeyCode::Enter),
+                Binding::Mouse(MouseButton::Left),
+                Binding::Gamepad(GamepadButton::South),
+                Binding::Touch,
+            ],
+            back: vec![
+                Binding::Key(KeyCode::Escape),
+                Binding::Key(KeyCode::Backspace),
+                Binding::Gamepad(GamepadButton::East),
+            ],
+        }
+    }
+}
+
+impl Bindings {
+    pub fn get(&self, action: Action) -> &[Binding] {
+        match action {
+            Action::Flap => &self.flap,
+            Action::Pause => &self.pause,
+            Action::Restart => &self.restart,
+            Action::Confirm => &self.confirm,
+            Action::Back => &self.back,
+        }
+    }
+
+    fn get_mut(&mut self, action: Action) -> &mut Vec<Binding> {
+        match action {
+            Action::Flap => &mut self.flap,
+            Action::Pause => &mut self.pause,
+            Action::Restart => &mut self.restart,
+            Action::Confirm => &mut self.confirm,
+            Action::Back => &mut self.back,
+        }
+    }
+
+    pub fn rebind(&mut self, action: Action, binding: Binding) {
+        let bindings = self.get_mut(action);
+        bindings.retain(|bound| !bound.same_device(&binding));
+        bindings.push(binding);
+    }
+
+    pub fn reset(&mut self, action: Action) {
+        *self.get_mut(action) = Bindings::default().get(action).to_vec();
+    }
+
+    //the first binding, for hints such as "press [space] to start."
+    pub fn label(&self, action: Action) -> String {
+        self.get(action)
+            .first()
+            .map_or_else(|| String::from("unbound"), Binding::to_string)
+    }
+
+    //every binding of an action, for the options screen
+    pub fn list(&self, action: Action) -> String {
+        let bindings = self.get(action);
+        if bindings.is_empty() {
+            return String::from("unbound");
+        }
+        bindings
+            .iter()
+            .map(Binding::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+//actions triggered this frame, read instead of the devices themselves
+#[derive(Resource, Default)]
+pub struct Actions {
+    just_pressed: HashSet<Action>,
+}
+
+impl Actions {
+    pub fn just_pressed(&self, action: Action) -> bool {
+        self.just_pressed.contains(&action)
+    }
+}
+
+//every device an action can be bound to
+#[derive(SystemParam)]
+pub struct InputDevices<'w, 's> {
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    gamepads: Query<'w, 's, &'static Gamepad>,
+    touches: Res<'w, Touches>,
+}
+
+impl InputDevices<'_, '_> {
+    pub fn just_pressed(&self, binding: Binding) -> bool {
+        match binding {
+            //alt+enter toggles fullscreen instead
+            Binding::Key(KeyCode::Enter)
+                if self.keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]) =>
+            {
+                false
+            }
+            Binding::Key(key) => self.keys.just_pressed(key),
+            Binding::Mouse(button) => self.mouse.just_pressed(button),
+            Binding::Gamepad(button) => self
+                .gamepads
+                .iter()
+                .any(|gamepad| gamepad.just_pressed(button)),
+            Binding::Touch => self.touches.any_just_pressed(),
+        }
+    }
+
+    //whatever was pressed this frame, for rebinding
+    pub fn any_just_pressed(&self) -> Option<Binding> {
+        self.keys
+            .get_just_pressed()
+            .next()
+            .map(|key| Binding::Key(*key))
+            .or_else(|| {
+                self.mouse
+                    .get_just_pressed()
+                    .next()
+                    .map(|button| Binding::Mouse(*button))
+            })
+            .or_else(|| {
+                self.gamepads
+                    .iter()
+                    .find_map(|gamepad| gamepad.get_just_pressed().next())
+                    .map(|button| Binding::Gamepad(*button))
+            })
+            .or_else(|| self.touches.any_just_pressed().then_some(Binding::Touch))
+    }
+}
+
+pub struct ActionPlugin;
+
+impl Plugin for ActionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Settings::load().bindings)
+            .init_resource::<Actions>()
+            .add_systems(PreUpdate, read_actions.after(InputSystem));
+    }
+}
+
+fn read_actions(devices: InputDevices, bindings: Res<Bindings>, mut actions: ResMut<Actions>) {
+    actions.just_pressed.clear();
+    for action in Action::ALL {
+        if bindings
+            .get(action)
+            .iter()
+            .any(|binding| devices.just_pressed(*binding))
+        {
+            actions.just_pressed.insert(action);
+        }
+    }
+}
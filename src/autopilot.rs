@@ -0,0 +1,214 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    bird::{Bird, FlapInput, BIRD_SIZE},
+    cli::Args,
+    config::{srgb, FlappConfig},
+    obstacles::{PipePair, OBSTACLE_WIDTH},
+    physics::{PhysicsSet, PhysicsTick, PhysicsTransform},
+    replay::{record_flaps, ReplayPlayback},
+    rng::GameRng,
+    score::{SCORE_POS_PAD_X, SCORE_POS_PAD_Y, SCORE_TEXT_SIZE},
+    GameState, WIN_X, WIN_Y,
+};
+
+//autopilot
+pub const AUTOPILOT_KEY: KeyCode = KeyCode::KeyA;
+pub const AUTOPILOT_TEXT: &str = "autopilot";
+//seconds spent on the title and game over screens before the autopilot starts the next run
+pub const AUTOPILOT_RESTART_DELAY: f32 = 2.;
+
+//a bot flying the bird, aiming for the middle of the next gap
+#[derive(Resource)]
+pub struct Autopilot {
+    pub enabled: bool,
+    //the bot flew some of the current run, which keeps it off the leaderboard
+    pub used: bool,
+    //decisions on their way to the wings, the oldest is acted on each tick
+    decisions: VecDeque<bool>,
+    //pair being aimed at and how far off its gap centre the bot aims
+    aim: Option<(Entity, f32)>,
+    //separate from `GameRng` so the bot doesn't change which pipes come up
+    rng: ChaCha8Rng,
+}
+
+impl Autopilot {
+    pub fn new(enabled: bool) -> Self {
+        Autopilot {
+            enabled,
+            used: false,
+            decisions: VecDeque::new(),
+            aim: None,
+            rng: ChaCha8Rng::seed_from_u64(0),
+        }
+    }
+
+    //the bot misses the same way every time on the same seed
+    fn start_run(&mut self, seed: u64) {
+        self.used = false;
+        self.decisions.clear();
+        self.aim = None;
+        self.rng = ChaCha8Rng::seed_from_u64(seed);
+    }
+}
+
+#[derive(Component)]
+pub struct AutopilotText;
+
+pub struct AutopilotPlugin;
+
+impl Plugin for AutopilotPlugin {
+    fn build(&self, app: &mut App) {
+        let enabled = app
+            .world()
+            .get_resource::<Args>()
+            .is_some_and(|args| args.autopilot);
+        app.insert_resource(Autopilot::new(enabled)).add_systems(
+            FixedUpdate,
+            fly_autopilot
+                .in_set(PhysicsSet::Input)
+                .before(record_flaps)
+                .run_if(not(resource_exists::<ReplayPlayback>)),
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn fly_autopilot(
+    mut autopilot: ResMut<Autopilot>,
+    bird_query: Query<(&Bird, &PhysicsTransform), Without<PipePair>>,
+    pair_query: Query<(Entity, &PipePair, &PhysicsTransform)>,
+    tick: Res<PhysicsTick>,
+    rng: Res<GameRng>,
+    config: Res<FlappConfig>,
+    time: Res<Time>,
+    mut flap_input: ResMut<FlapInput>,
+) {
+    if tick.0 == 0 {
+        autopilot.start_run(rng.seed());
+    }
+    if !autopilot.enabled {
+        autopilot.decisions.clear();
+        return;
+    }
+    autopilot.used = true;
+    let Ok((bird, transform)) = bird_query.get_single() else {
+        return;
+    };
+
+    //the nearest pair the bird isn't clear of yet
+    let reach = (OBSTACLE_WIDTH + BIRD_SIZE.x) * config.pixel_ratio / 2.;
+    let next = pair_query
+        .iter()
+        .filter(|(_, _, pair_transform)| {
+            pair_transform.translation.x + reach > transform.translation.x
+        })
+        .min_by(|(_, _, a), (_, _, b)| a.translation.x.total_cmp(&b.translation.x));
+    let target = match next {
+        Some((entity, _, pair_transform)) => {
+            let offset = match autopilot.aim {
+                Some((aimed, offset)) if aimed == entity => offset,
+                _ => {
+                    let noise = config.autopilot_aim_noise * config.pixel_ratio;
+                    let offset = if noise > 0. {
+                        autopilot.rng.gen_range(-noise..noise)
+                    } else {
+                        0.
+                    };
+                    autopilot.aim = Some((entity, offset));
+                    offset
+                }
+            };
+            pair_transform.translation.y + offset
+        }
+        None => 0.,
+    };
+
+    //a flap lifts the bird this high, flapping half of it under the target keeps it bobbing around it
+    let rise = (config.flap_force.powi(2) / (2. * config.gravity))
+        .min(config.obstacle_gap * config.pixel_ratio);
+    //judged by where the bird will be once the flap lands, as long as nothing else is on its way
+    let delay = (config.autopilot_reaction / time.delta_secs()).round() as usize;
+    let lead = delay as f32 * time.delta_secs();
+    let landing_velocity = bird.velocity - config.gravity * lead;
+    let landing_y = transform.translation.y + (bird.velocity + landing_velocity) / 2. * lead;
+    let flap = landing_y < target - rise / 2.
+        && landing_velocity <= 0.
+        && !autopilot.decisions.contains(&true);
+    autopilot.decisions.push_back(flap);
+
+    while autopilot.decisions.len() > delay {
+        if autopilot.decisions.pop_front() == Some(true) {
+            flap_input.pending = true;
+        }
+    }
+}
+
+pub(crate) fn toggle_autopilot(keys: Res<ButtonInput<KeyCode>>, mut autopilot: ResMut<Autopilot>) {
+    if keys.just_pressed(AUTOPILOT_KEY) {
+        autopilot.enabled = !autopilot.enabled;
+    }
+}
+
+//attract mode, the bot keeps playing run after run on its own
+pub(crate) fn restart_with_autopilot(
+    time: Res<Time>,
+    autopilot: Res<Autopilot>,
+    state: Res<State<GameState>>,
+    mut waited: Local<f32>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if state.is_changed() || !autopilot.enabled {
+        *waited = 0.;
+        return;
+    }
+    *waited += time.delta_secs();
+    if *waited >= AUTOPILOT_RESTART_DELAY {
+        next_state.set(GameState::Countdown);
+    }
+}
+
+//shown under the score while the bot is flying
+pub(crate) fn spawn_autopilot_text(
+    mut commands: Commands,
+    config: Res<FlappConfig>,
+    autopilot: Res<Autopilot>,
+) {
+    commands.spawn((
+        Text2d::new(AUTOPILOT_TEXT),
+        TextFont {
+            font_size: SCORE_TEXT_SIZE / 2.,
+            ..Default::default()
+        },
+        TextColor(srgb(config.score_text_color)),
+        Transform::from_xyz(
+            -WIN_X / 2. + SCORE_POS_PAD_X * config.pixel_ratio,
+            WIN_Y / 2. - (SCORE_POS_PAD_Y + SCORE_TEXT_SIZE) * config.pixel_ratio,
+            1.,
+        )
+        .with_scale(Vec3::splat(config.pixel_ratio)),
+        autopilot_visibility(&autopilot),
+        AutopilotText,
+    ));
+}
+
+pub(crate) fn show_autopilot_text(
+    autopilot: Res<Autopilot>,
+    mut query: Query<&mut Visibility, With<AutopilotText>>,
+) {
+    for mut visibility in query.iter_mut() {
+        visibility.set_if_neq(autopilot_visibility(&autopilot));
+    }
+}
+
+fn autopilot_visibility(autopilot: &Autopilot) -> Visibility {
+    if autopilot.enabled {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
@@ -0,0 +1,274 @@
+use bevy::prelude::*;
+
+use crate::{
+    actions::{Action, Actions},
+    collision::{bird_hits_pair, CollisionMasks},
+    config::{Ceiling, FlappConfig},
+    obstacles::{PipePair, OBSTACLE_WIDTH},
+    physics::{physics_transform, PhysicsSet, PhysicsTransform, PreviousPhysicsTransform},
+    score::Score,
+    training::Training,
+    GameManager, GameState, SpriteImages,
+};
+
+//bird
+pub const VELOCITY_ROT_RATIO: f32 = 7.2;
+pub const BIRD_SIZE: Vec2 = Vec2::new(12., 8.);
+//share of the upward speed kept when knocked off a bouncing ceiling
+pub const CEILING_BOUNCE: f32 = 0.5;
+
+#[derive(Component)]
+pub struct Bird {
+    pub velocity: f32,
+}
+
+//a flap pressed since the last physics tick, used up by the next one
+#[derive(Resource, Default)]
+pub struct FlapInput {
+    pub pending: bool,
+}
+
+//what ended the last run
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DeathCause {
+    Ground,
+    Ceiling,
+    //the pair and which of its pipes was hit
+    Pipe { pair: Entity, pipe_direction: f32 },
+}
+
+#[derive(Resource, Default)]
+pub struct LastDeath(pub Option<DeathCause>);
+
+pub struct BirdPlugin;
+
+impl Plugin for BirdPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlapInput>()
+            .init_resource::<LastDeath>()
+            .add_systems(OnEnter(GameState::Countdown), forget_death)
+            .add_systems(
+                Update,
+                apply_config_to_bird.run_if(resource_changed::<FlappConfig>),
+            )
+            .add_systems(
+                FixedUpdate,
+                //a training population flies itself
+                update_bird
+                    .in_set(PhysicsSet::Bird)
+                    .run_if(not(resource_exists::<Training>)),
+            );
+    }
+}
+
+pub fn spawn_bird(commands: &mut Commands, config: &FlappConfig, scale: f32) {
+    commands.spawn((
+        Transform::IDENTITY.with_scale(Vec3::splat(config.pixel_ratio * scale)),
+        physics_transform(Vec2::ZERO),
+        Bird { velocity: 0. },
+    ));
+}
+
+//rescale the bird when the pixel ratio is edited while running
+fn apply_config_to_bird(
+    config: Res<FlappConfig>,
+    mut previous_ratio: Local<Option<f32>>,
+    mut bird_query: Query<
+        (
+            &mut PhysicsTransform,
+            &mut PreviousPhysicsTransform,
+            &mut Transform,
+        ),
+        With<Bird>,
+    >,
+) {
+    let Some(old_ratio) = previous_ratio.replace(config.pixel_ratio) else {
+        return;
+    };
+    if old_ratio == config.pixel_ratio {
+        return;
+    }
+    for (mut physics, mut previous, mut transform) in bird_query.iter_mut() {
+        physics.translation.y *= config.pixel_ratio / old_ratio;
+        previous.0 = *physics;
+        transform.scale = Vec3::splat(config.pixel_ratio);
+    }
+}
+
+//birds are spawned bare by the simulation, the sprite is only added when there is something to draw to
+pub(crate) fn attach_bird_sprites(
+    mut commands: Commands,
+    sprite_images: Res<SpriteImages>,
+    bird_query: Query<Entity, (With<Bird>, Without<Sprite>)>,
+) {
+    for entity in bird_query.iter() {
+        commands.entity(entity).insert(Sprite {
+            image: sprite_images.bird.clone(),
+            ..Default::default()
+        });
+    }
+}
+
+pub(crate) fn read_flap_input(actions: Res<Actions>, mut flap_input: ResMut<FlapInput>) {
+    if actions.just_pressed(Action::Flap) {
+        flap_input.pending = true;
+    }
+}
+
+//one tick of flight, shared by the bird and anything replaying its moves
+pub fn fly(
+    velocity: &mut f32,
+    transform: &mut PhysicsTransform,
+    flap: bool,
+    config: &FlappConfig,
+    delta: f32,
+) {
+    if flap {
+        *velocity = config.flap_force;
+    }
+
+    *velocity -= delta * config.gravity;
+    transform.translation.y += *velocity * delta;
+    transform.rotation = f32::clamp(*velocity / VELOCITY_ROT_RATIO, -90., 90.).to_radians();
+}
+
+fn forget_death(mut last_death: ResMut<LastDeath>) {
+    last_death.0 = None;
+}
+
+//applies the configured ceiling, returns whether the bird hit a deadly one
+pub fn hit_ceiling(
+    velocity: &mut f32,
+    transform: &mut PhysicsTransform,
+    config: &FlappConfig,
+    window_height: f32,
+) -> bool {
+    let top = window_height / 2. - BIRD_SIZE.y * config.pixel_ratio / 2.;
+    if transform.translation.y < top {
+        return false;
+    }
+    match config.ceiling {
+        Ceiling::Kill => true,
+        Ceiling::Bounce => {
+            transform.translation.y = top;
+            *velocity = -velocity.abs() * CEILING_BOUNCE;
+            false
+        }
+        Ceiling::Open => false,
+    }
+}
+
+//what ends the run of a bird that moved from `previous` to `transform` this tick, if anything,
+//shared by every system flying birds so they all live by the same rules
+pub fn tick_death<'a>(
+    velocity: &mut f32,
+    previous: &PhysicsTransform,
+    transform: &mut PhysicsTransform,
+    pairs: impl IntoIterator<
+        Item = (
+            Entity,
+            &'a PipePair,
+            &'a PhysicsTransform,
+            &'a PreviousPhysicsTransform,
+        ),
+    >,
+    config: &FlappConfig,
+    masks: &CollisionMasks,
+    window_height: f32,
+) -> Option<DeathCause> {
+    if hit_ceiling(velocity, transform, config, window_height) {
+        return Some(DeathCause::Ceiling);
+    }
+    if transform.translation.y <= -window_height / 2. {
+        return Some(DeathCause::Ground);
+    }
+    pairs
+        .into_iter()
+        .find(|(_, pair, pair_transform, pair_previous)| {
+            bird_hits_pair(
+                previous,
+                transform,
+                pair_previous.0.translation,
+                pair_transform.translation,
+                pair,
+                config,
+                masks,
+            )
+        })
+        .map(|(entity, _, pair_transform, _)| DeathCause::Pipe {
+            pair: entity,
+            //the bird can only reach the pipe on its own side of the gap
+            pipe_direction: if transform.translation.y > pair_transform.translation.y {
+                1.
+            } else {
+                -1.
+            },
+        })
+}
+
+//a point per pair once a bird at `bird_x` is past its trailing edge
+pub fn passes_pair(bird_x: f32, pair: &PipePair, pair_x: f32, config: &FlappConfig) -> bool {
+    !pair.passed && bird_x > pair_x + OBSTACLE_WIDTH * config.pixel_ratio / 2.
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_bird(
+    mut bird_query: Query<
+        (&mut Bird, &mut PhysicsTransform, &PreviousPhysicsTransform),
+        Without<PipePair>,
+    >,
+    mut pair_query: Query<(
+        Entity,
+        &mut PipePair,
+        &PhysicsTransform,
+        &PreviousPhysicsTransform,
+    )>,
+    time: Res<Time>,
+    mut flap_input: ResMut<FlapInput>,
+    game_manager: Res<GameManager>,
+    config: Res<FlappConfig>,
+    masks: Res<CollisionMasks>,
+    mut score: ResMut<Score>,
+    mut last_death: ResMut<LastDeath>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Ok((mut bird, mut transform, previous)) = bird_query.get_single_mut() else {
+        return;
+    };
+    let flap = std::mem::take(&mut flap_input.pending);
+    fly(
+        &mut bird.velocity,
+        &mut transform,
+        flap,
+        &config,
+        time.delta_secs(),
+    );
+
+    let death = tick_death(
+        &mut bird.velocity,
+        &previous.0,
+        &mut transform,
+        pair_query.iter(),
+        &config,
+        &masks,
+        game_manager.window_dimensions.y,
+    );
+    if death.is_none() {
+        for (_, mut pair, pair_transform, _) in pair_query.iter_mut() {
+            if passes_pair(
+                transform.translation.x,
+                &pair,
+                pair_transform.translation.x,
+                &config,
+            ) {
+                pair.passed = true;
+                score.value += 1;
+            }
+        }
+    }
+
+    if death.is_some() {
+        last_death.0 = death;
+        next_state.set(GameState::GameOver);
+    }
+}
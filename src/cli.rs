@@ -0,0 +1,142 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use crate::{remote::RemoteAddress, training::MIN_POPULATION};
+
+pub const USAGE: &str = "usage: flapp [--seed <number> | --replay <file>] [--record <file>] \
+[--ghost <file>] [--remote <port | address | unix:path>] [--autopilot] [--headless [--runs <number>] | --stdio] \
+[--train [--population <number>] [--generations <number>]]";
+
+//command line options of the game binary
+#[derive(Resource, Clone, Debug, Default)]
+pub struct Args {
+    pub seed: Option<u64>,
+    //no window, games are simulated back to back and reported on stdout
+    pub headless: bool,
+    pub runs: Option<u64>,
+    //every finished run is saved here
+    pub record: Option<PathBuf>,
+    //play a recorded run back instead of reading input
+    pub replay: Option<PathBuf>,
+    //race a recorded run, its seed is used for every run
+    pub ghost: Option<PathBuf>,
+    //no window, the game is driven one tick at a time by JSON lines on stdin and answers on stdout
+    pub stdio: bool,
+    //let local clients watch and play the running game over a socket
+    pub remote: Option<RemoteAddress>,
+    //start with the bot flying, toggled in game
+    pub autopilot: bool,
+    //evolve a population of network flown birds instead of playing
+    pub train: bool,
+    pub population: Option<u64>,
+    //stop training after this many generations
+    pub generations: Option<u64>,
+}
+
+impl Args {
+    pub fn parse() -> Result<Self, String> {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    pub fn parse_from(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => parsed.seed = Some(number(&arg, args.next())?),
+                "--headless" => parsed.headless = true,
+                "--runs" => parsed.runs = Some(number(&arg, args.next())?),
+                "--record" => parsed.record = Some(path(&arg, args.next())?),
+                "--replay" => parsed.replay = Some(path(&arg, args.next())?),
+                "--ghost" => parsed.ghost = Some(path(&arg, args.next())?),
+                "--stdio" => parsed.stdio = true,
+                "--autopilot" => parsed.autopilot = true,
+                "--train" => parsed.train = true,
+                "--population" => parsed.population = Some(number(&arg, args.next())?),
+                "--generations" => parsed.generations = Some(number(&arg, args.next())?),
+                "--remote" => {
+                    let value = args.next().ok_or("`--remote` needs an address")?;
+                    parsed.remote = Some(value.parse()?);
+                }
+                _ => return Err(format!("unknown argument `{arg}`")),
+            }
+        }
+        if parsed.seed.is_some() && parsed.replay.is_some() {
+            return Err(String::from(
+                "`--seed` and `--replay` can't be combined, replays bring their own seed",
+            ));
+        }
+        if parsed.autopilot && parsed.replay.is_some() {
+            return Err(String::from(
+                "`--autopilot` and `--replay` can't be combined, the replay does the flying",
+            ));
+        }
+        if parsed.seed.is_some() && parsed.ghost.is_some() {
+            return Err(String::from(
+                "`--seed` and `--ghost` can't be combined, the ghost brings its own seed",
+            ));
+        }
+        if parsed.ghost.is_some() && parsed.headless {
+            return Err(String::from("`--ghost` needs a window"));
+        }
+        if parsed.stdio
+            && (parsed.headless
+                || parsed.replay.is_some()
+                || parsed.record.is_some()
+                || parsed.ghost.is_some()
+                || parsed.remote.is_some()
+                || parsed.autopilot)
+        {
+            return Err(String::from(
+                "`--stdio` only combines with `--seed`, runs are driven by the commands",
+            ));
+        }
+        if parsed.train
+            && (parsed.replay.is_some()
+                || parsed.record.is_some()
+                || parsed.ghost.is_some()
+                || parsed.autopilot
+                || parsed.stdio
+                || parsed.remote.is_some()
+                || parsed.runs.is_some())
+        {
+            return Err(String::from(
+                "`--train` only combines with `--seed` and `--headless`, the birds fly themselves",
+            ));
+        }
+        if (parsed.population.is_some() || parsed.generations.is_some()) && !parsed.train {
+            return Err(String::from(
+                "`--population` and `--generations` only apply with `--train`",
+            ));
+        }
+        if parsed
+            .population
+            .is_some_and(|population| population < MIN_POPULATION as u64)
+        {
+            return Err(format!(
+                "`--population` needs at least {MIN_POPULATION} birds to breed"
+            ));
+        }
+        if parsed.generations == Some(0) {
+            return Err(String::from("`--generations` needs at least 1 generation"));
+        }
+        if parsed.runs.is_some() && !parsed.headless {
+            return Err(String::from("`--runs` only applies with `--headless`"));
+        }
+        Ok(parsed)
+    }
+}
+
+fn number(flag: &str, value: Option<String>) -> Result<u64, String> {
+    let value = value.ok_or_else(|| format!("`{flag}` needs a value"))?;
+    value
+        .parse()
+        .map_err(|_| format!("`{flag}` expects a number, got `{value}`"))
+}
+
+fn path(flag: &str, value: Option<String>) -> Result<PathBuf, String> {
+    value
+        .map(PathBuf::from)
+        .ok_or_else(|| format!("`{flag}` needs a file"))
+}
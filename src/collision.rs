@@ -0,0 +1,324 @@
+use bevy::{
+    image::{CompressedImageFormats, ImageSampler, ImageType},
+    prelude::*,
+    render::render_asset::RenderAssetUsages,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bird::BIRD_SIZE,
+    config::FlappConfig,
+    obstacles::{PipePair, OBSTACLE_WIDTH},
+    physics::PhysicsTransform,
+};
+
+//the art the pixel masks are cut from, embedded so the simulation needs no asset server
+const BIRD_PNG: &[u8] = include_bytes!("../assets/bird.png");
+const PIPE_PNG: &[u8] = include_bytes!("../assets/pipe.png");
+
+//shape the bird collides with
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Hitbox {
+    //as tall as the bird, ignores its rotation
+    Circle,
+    //rounded box along the bird, turns with it
+    Capsule,
+    //the opaque pixels of the bird and pipe art
+    PixelMask,
+}
+
+//which pixels of an image are drawn
+pub struct AlphaMask {
+    width: usize,
+    height: usize,
+    opaque: Vec<bool>,
+}
+
+impl AlphaMask {
+    pub fn from_png(bytes: &[u8]) -> Self {
+        let image = Image::from_buffer(
+            bytes,
+            ImageType::Extension("png"),
+            CompressedImageFormats::NONE,
+            true,
+            ImageSampler::Default,
+            RenderAssetUsages::MAIN_WORLD,
+        )
+        .expect("embedded art is a valid png");
+        let opaque = image.data.chunks_exact(4).map(|rgba| rgba[3] > 0).collect();
+        AlphaMask {
+            width: image.width() as usize,
+            height: image.height() as usize,
+            opaque,
+        }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.width as f32, self.height as f32)
+    }
+
+    //`pixel` counts from the top left corner, everything outside the image is clear
+    pub fn is_opaque(&self, pixel: Vec2) -> bool {
+        if pixel.x < 0. || pixel.y < 0. {
+            return false;
+        }
+        let (x, y) = (pixel.x as usize, pixel.y as usize);
+        x < self.width && y < self.height && self.opaque[y * self.width + x]
+    }
+}
+
+#[derive(Resource)]
+pub struct CollisionMasks {
+    pub bird: AlphaMask,
+    pub pipe: AlphaMask,
+}
+
+impl Default for CollisionMasks {
+    fn default() -> Self {
+        CollisionMasks {
+            bird: AlphaMask::from_png(BIRD_PNG),
+            pipe: AlphaMask::from_png(PIPE_PNG),
+        }
+    }
+}
+
+pub struct CollisionPlugin;
+
+impl Plugin for CollisionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CollisionMasks>();
+    }
+}
+
+//top and bottom pipe of a pair, trimmed by `mercy_zone` art pixels and reaching off forever
+pub fn pipe_rects(
+    pair_translation: Vec2,
+    pair: &PipePair,
+    pixel_ratio: f32,
+    mercy_zone: f32,
+) -> [Rect; 2] {
+    let half_width = (OBSTACLE_WIDTH - mercy_zone) * pixel_ratio / 2.;
+    let gap_edge = pair.gap_size / 2. + mercy_zone * pixel_ratio / 2.;
+    let (left, right) = (
+        pair_translation.x - half_width,
+        pair_translation.x + half_width,
+    );
+    [
+        Rect {
+            min: Vec2::new(left, pair_translation.y + gap_edge),
+            max: Vec2::new(right, f32::INFINITY),
+        },
+        Rect {
+            min: Vec2::new(left, f32::NEG_INFINITY),
+            max: Vec2::new(right, pair_translation.y - gap_edge),
+        },
+    ]
+}
+
+//whether the bird touches the pair anywhere on the way from the start of the tick to its end,
+//so a long tick can't carry it through a pipe
+pub fn bird_hits_pair(
+    bird_start: &PhysicsTransform,
+    bird_end: &PhysicsTransform,
+    pair_start: Vec2,
+    pair_end: Vec2,
+    pair: &PipePair,
+    config: &FlappConfig,
+    masks: &CollisionMasks,
+) -> bool {
+    //the pair stands still and the bird moves relative to it
+    let start = PhysicsTransform {
+        translation: bird_start.translation - pair_start,
+        rotation: bird_start.rotation,
+    };
+    let end = PhysicsTransform {
+        translation: bird_end.translation - pair_end,
+        rotation: bird_end.rotation,
+    };
+    let rects = pipe_rects(Vec2::ZERO, pair, config.pixel_ratio, config.mercy_zone);
+    let radius = BIRD_SIZE.y * config.pixel_ratio / 2.;
+    let turn = (end.rotation - start.rotation).abs();
+    match config.hitbox {
+        Hitbox::Circle => rects.iter().any(|rect| {
+            swept_distance(
+                [start.translation; 2],
+                end.translation - start.translation,
+                *rect,
+            ) < radius
+        }),
+        Hitbox::Capsule => {
+            let half_length = (BIRD_SIZE.x - BIRD_SIZE.y) * config.pixel_ratio / 2.;
+            //turning isn't swept, split it up so the ends move less than a quarter radius each step
+            let steps = (turn * half_length / (radius / 4.)).ceil().max(1.) as usize;
+            (0..steps).any(|step| {
+                let from = lerp_transform(&start, &end, step as f32 / steps as f32);
+                let to = lerp_transform(&start, &end, (step + 1) as f32 / steps as f32);
+                let motion = to.translation - from.translation;
+                [from.rotation, to.rotation].into_iter().any(|rotation| {
+                    let axis = Vec2::from_angle(rotation) * half_length;
+                    let segment = [from.translation - axis, from.translation + axis];
+                    rects
+                        .iter()
+                        .any(|rect| swept_distance(segment, motion, *rect) < radius)
+                })
+            })
+        }
+        Hitbox::PixelMask => {
+            //sample the path finely enough that no pixel skips half a pixel of pipe
+            let furthest = BIRD_SIZE.length() / 2. * config.pixel_ratio;
+            let travel = start.translation.distance(end.translation) + turn * furthest;
+            let steps = (travel / (config.pixel_ratio / 2.)).ceil().max(1.) as usize;
+            (0..=steps).any(|step| {
+                let t = step as f32 / steps as f32;
+                bird_pixels(&lerp_transform(&start, &end, t), config, &masks.bird).any(|point| {
+                    [1., -1.]
+                        .into_iter()
+                        .zip(rects)
+                        .any(|(pipe_direction, rect)| {
+                            rect.contains(point)
+                                && pipe_pixel(
+                                    point,
+                                    Vec2::ZERO,
+                                    pair,
+                                    pipe_direction,
+                                    config,
+                                    masks,
+                                )
+                        })
+                })
+            })
+        }
+    }
+}
+
+fn lerp_transform(start: &PhysicsTransform, end: &PhysicsTransform, t: f32) -> PhysicsTransform {
+    PhysicsTransform {
+        translation: start.translation.lerp(end.translation, t),
+        rotation: start.rotation + (end.rotation - start.rotation) * t,
+    }
+}
+
+//centres of the opaque bird pixels in the world
+pub fn bird_pixels<'a>(
+    bird: &'a PhysicsTransform,
+    config: &'a FlappConfig,
+    mask: &'a AlphaMask,
+) -> impl Iterator<Item = Vec2> + 'a {
+    let rotation = Vec2::from_angle(bird.rotation);
+    let half_size = mask.size() / 2.;
+    (0..mask.height).flat_map(move |y| {
+        (0..mask.width).filter_map(move |x| {
+            let pixel = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+            mask.is_opaque(pixel).then(|| {
+                let local = Vec2::new(pixel.x - half_size.x, half_size.y - pixel.y);
+                bird.translation + rotation.rotate(local * config.pixel_ratio)
+            })
+        })
+    })
+}
+
+//whether the pipe art facing `pipe_direction` is drawn at `point`
+fn pipe_pixel(
+    point: Vec2,
+    pair_translation: Vec2,
+    pair: &PipePair,
+    pipe_direction: f32,
+    config: &FlappConfig,
+    masks: &CollisionMasks,
+) -> bool {
+    let mask = &masks.pipe;
+    //rows count away from the cap on the gap, the stretched shaft repeats the last row
+    let cap_y = pair_translation.y + pair.gap_size / 2. * pipe_direction;
+    let row = ((point.y - cap_y) * pipe_direction / config.pixel_ratio).min(mask.size().y - 1.);
+    let column = (point.x - pair_translation.x) / config.pixel_ratio + mask.size().x / 2.;
+    mask.is_opaque(Vec2::new(column, row))
+}
+
+fn point_rect_distance(point: Vec2, rect: Rect) -> f32 {
+    point.distance(point.clamp(rect.min, rect.max))
+}
+
+fn point_segment_distance(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let t = if ab == Vec2::ZERO {
+        0.
+    } else {
+        ((point - a).dot(ab) / ab.length_squared()).clamp(0., 1.)
+    };
+    point.distance(a + ab * t)
+}
+
+//distance from the area `segment` covers while moving by `motion` to the rect
+fn swept_distance(segment: [Vec2; 2], motion: Vec2, rect: Rect) -> f32 {
+    let [a, b] = segment;
+    let corners = [a, b, b + motion, a + motion];
+    let edges = [
+        (a, b),
+        (b, b + motion),
+        (b + motion, a + motion),
+        (a + motion, a),
+    ];
+    let rect_corners: Vec<Vec2> = [
+        rect.min,
+        rect.max,
+        Vec2::new(rect.min.x, rect.max.y),
+        Vec2::new(rect.max.x, rect.min.y),
+    ]
+    .into_iter()
+    .filter(|corner| corner.is_finite())
+    .collect();
+
+    if edges
+        .iter()
+        .any(|&(from, to)| segment_hits_rect(from, to, rect))
+        || rect_corners
+            .iter()
+            .any(|&corner| in_parallelogram(corner, a, b - a, motion))
+    {
+        return 0.;
+    }
+    //apart, so the closest two points are a corner of one and a side of the other
+    let to_rect = corners
+        .into_iter()
+        .map(|corner| point_rect_distance(corner, rect));
+    let to_sweep = rect_corners.iter().flat_map(|&corner| {
+        edges
+            .iter()
+            .map(move |&(from, to)| point_segment_distance(corner, from, to))
+    });
+    to_rect.chain(to_sweep).fold(f32::INFINITY, f32::min)
+}
+
+//flat parallelograms have no inside, their edges are checked on their own
+fn in_parallelogram(point: Vec2, origin: Vec2, side: Vec2, other_side: Vec2) -> bool {
+    let area = side.perp_dot(other_side);
+    if area == 0. {
+        return false;
+    }
+    let offset = point - origin;
+    let u = offset.perp_dot(other_side) / area;
+    let v = side.perp_dot(offset) / area;
+    (0. ..=1.).contains(&u) && (0. ..=1.).contains(&v)
+}
+
+//clips the segment against the rect one axis at a time
+fn segment_hits_rect(a: Vec2, b: Vec2, rect: Rect) -> bool {
+    let direction = b - a;
+    let (mut enter, mut exit) = (0_f32, 1_f32);
+    for axis in 0..2 {
+        if direction[axis] == 0. {
+            if a[axis] < rect.min[axis] || a[axis] > rect.max[axis] {
+                return false;
+            }
+            continue;
+        }
+        let t0 = (rect.min[axis] - a[axis]) / direction[axis];
+        let t1 = (rect.max[axis] - a[axis]) / direction[axis];
+        enter = enter.max(t0.min(t1));
+        exit = exit.min(t0.max(t1));
+        if enter > exit {
+            return false;
+        }
+    }
+    true
+}
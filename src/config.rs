@@ -0,0 +1,312 @@
+use std::{fmt, fs, io, path::PathBuf, time::Duration};
+
+use bevy::{
+    asset::{io::AssetSource, io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{collision::Hitbox, obstacles::OBSTACLE_WIDTH};
+
+//file looked up next to the executable
+pub const CONFIG_FILE: &str = "flapp.ron";
+//asset source rooted next to the executable, so edits to the config file are picked up live
+pub const CONFIG_SOURCE: &str = "config";
+pub const CONFIG_DEBOUNCE: Duration = Duration::from_millis(300);
+
+//gameplay tuning, every field falls back to its default when left out of the file
+#[derive(Resource, Asset, TypePath, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlappConfig {
+    pub gravity: f32,
+    pub flap_force: f32,
+    pub scroll_speed: f32,
+    pub obstacle_spacing: f32,
+    pub obstacle_gap: f32,
+    pub obstacle_vertical_offset: f32,
+    //art pixels trimmed off the pipes, split between both sides
+    pub mercy_zone: f32,
+    pub pixel_ratio: f32,
+    pub hitbox: Hitbox,
+    //what happens when the bird reaches the top of the screen
+    pub ceiling: Ceiling,
+    //physics ticks per second, independent of the frame rate
+    pub tick_rate: f32,
+    pub background_color: [f32; 3],
+    pub pause_text_color: [f32; 3],
+    pub score_text_color: [f32; 3],
+    //black bars around the play field, otherwise the background color fills the window
+    pub letterbox: bool,
+    //seconds between the autopilot seeing the bird low and its flap landing
+    pub autopilot_reaction: f32,
+    //art px the autopilot may aim off the centre of each gap
+    pub autopilot_aim_noise: f32,
+    //fixed seed for pipe placement, a new one is picked every run when left empty
+    pub seed: Option<u64>,
+    //name put on the leaderboard, the login name when left empty
+    pub player_name: Option<String>,
+}
+
+impl Default for FlappConfig {
+    fn default() -> Self {
+        FlappConfig {
+            gravity: 1600.,
+            flap_force: 400.,
+            scroll_speed: 120.,
+            obstacle_spacing: 64.,
+            obstacle_gap: 16.,
+            obstacle_vertical_offset: 30.,
+            mercy_zone: 5.,
+            pixel_ratio: 4.5,
+            hitbox: Hitbox::Capsule,
+            ceiling: Ceiling::Bounce,
+            tick_rate: 60.,
+            background_color: [0.5, 0.7, 0.8],
+            pause_text_color: [1., 0.5, 0.2],
+            score_text_color: [1., 1., 0.],
+            letterbox: true,
+            autopilot_reaction: 0.05,
+            autopilot_aim_noise: 4.,
+            seed: None,
+            player_name: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Ceiling {
+    //the run ends like hitting the ground
+    Kill,
+    //the bird is held under the top edge and knocked back down
+    Bounce,
+    //the bird may leave the screen, the pipes reach up forever so it can't fly over them
+    Open,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Invalid { field: &'static str, reason: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "could not read config: {err}"),
+            ConfigError::Parse(err) => write!(f, "could not parse config: {err}"),
+            ConfigError::Invalid { field, reason } => write!(f, "`{field}` {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+//registers the `config://` asset source, has to be added before `DefaultPlugins`
+pub struct ConfigSourcePlugin;
+
+impl Plugin for ConfigSourcePlugin {
+    fn build(&self, app: &mut App) {
+        let Some(dir) = FlappConfig::path()
+            .as_ref()
+            .and_then(|path| path.parent())
+            .map(|dir| dir.to_string_lossy().into_owned())
+        else {
+            return;
+        };
+        app.register_asset_source(
+            CONFIG_SOURCE,
+            AssetSource::build()
+                .with_reader(AssetSource::get_default_reader(dir.clone()))
+                .with_watcher(AssetSource::get_default_watcher(dir, CONFIG_DEBOUNCE)),
+        );
+    }
+}
+
+//inserts the `FlappConfig` resource and keeps it in sync with the file when `ConfigSourcePlugin` is present
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world().contains_resource::<FlappConfig>() {
+            app.insert_resource(FlappConfig::load());
+        }
+        let hot_reload = app
+            .world()
+            .get_resource::<AssetServer>()
+            .is_some_and(|asset_server| asset_server.get_source(CONFIG_SOURCE).is_ok());
+        if hot_reload {
+            app.init_asset::<FlappConfig>()
+                .register_asset_loader(FlappConfigLoader)
+                .add_systems(Startup, watch_config)
+                .add_systems(PreUpdate, reload_config);
+        }
+    }
+}
+
+#[derive(Resource)]
+struct ConfigHandle(Handle<FlappConfig>);
+
+#[derive(Default)]
+struct FlappConfigLoader;
+
+impl AssetLoader for FlappConfigLoader {
+    type Asset = FlappConfig;
+    type Settings = ();
+    type Error = ConfigError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<FlappConfig, ConfigError> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(ConfigError::Io)?;
+        let text = String::from_utf8_lossy(&bytes);
+        FlappConfig::from_ron(&text)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+fn watch_config(mut commands: Commands, asset_server: Res<AssetServer>) {
+    //without a file there is nothing to watch, and the defaults are already in place
+    if !FlappConfig::path().is_some_and(|path| path.exists()) {
+        return;
+    }
+    let handle = asset_server.load(format!("{CONFIG_SOURCE}://{CONFIG_FILE}"));
+    commands.insert_resource(ConfigHandle(handle));
+}
+
+//swap in the new tuning whenever the watched file changes, broken edits are logged by the loader and ignored
+fn reload_config(
+    mut events: EventReader<AssetEvent<FlappConfig>>,
+    handle: Option<Res<ConfigHandle>>,
+    assets: Res<Assets<FlappConfig>>,
+    mut config: ResMut<FlappConfig>,
+) {
+    let Some(handle) = handle else {
+        return;
+    };
+    for event in events.read() {
+        if let AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id } = event {
+            if *id != handle.0.id() {
+                continue;
+            }
+            if let Some(loaded) = assets.get(*id) {
+                if *loaded != *config {
+                    info!("reloaded {CONFIG_FILE}");
+                    *config = loaded.clone();
+                }
+            }
+        }
+    }
+}
+
+impl FlappConfig {
+    pub fn path() -> Option<PathBuf> {
+        let exe = std::env::current_exe().ok()?;
+        Some(exe.parent()?.join(CONFIG_FILE))
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, ConfigError> {
+        let config: FlappConfig = ron::from_str(text).map_err(ConfigError::Parse)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    //config file next to the executable, or the defaults if there is none
+    pub fn try_load() -> Result<Self, ConfigError> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => Self::from_ron(&text),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(ConfigError::Io(err)),
+        }
+    }
+
+    //same, with a broken config logged and replaced by the defaults
+    pub fn load() -> Self {
+        Self::try_load().unwrap_or_else(|err| {
+            error!("{CONFIG_FILE}: {err}, using default config");
+            Self::default()
+        })
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        positive("gravity", self.gravity, true)?;
+        positive("flap_force", self.flap_force, false)?;
+        positive("scroll_speed", self.scroll_speed, false)?;
+        positive("obstacle_gap", self.obstacle_gap, true)?;
+        positive(
+            "obstacle_vertical_offset",
+            self.obstacle_vertical_offset,
+            true,
+        )?;
+        positive("mercy_zone", self.mercy_zone, true)?;
+        positive("pixel_ratio", self.pixel_ratio, false)?;
+        positive("tick_rate", self.tick_rate, false)?;
+        positive("autopilot_reaction", self.autopilot_reaction, true)?;
+        positive("autopilot_aim_noise", self.autopilot_aim_noise, true)?;
+        if self.obstacle_spacing < OBSTACLE_WIDTH || !self.obstacle_spacing.is_finite() {
+            return Err(invalid(
+                "obstacle_spacing",
+                format!(
+                    "must be at least the pipe width {OBSTACLE_WIDTH} (got {})",
+                    self.obstacle_spacing
+                ),
+            ));
+        }
+        if self.mercy_zone >= OBSTACLE_WIDTH {
+            return Err(invalid(
+                "mercy_zone",
+                format!(
+                    "must be less than the pipe width {OBSTACLE_WIDTH} (got {})",
+                    self.mercy_zone
+                ),
+            ));
+        }
+        color("background_color", self.background_color)?;
+        color("pause_text_color", self.pause_text_color)?;
+        color("score_text_color", self.score_text_color)?;
+        Ok(())
+    }
+}
+
+pub fn srgb(rgb: [f32; 3]) -> Color {
+    Color::srgb(rgb[0], rgb[1], rgb[2])
+}
+
+fn invalid(field: &'static str, reason: String) -> ConfigError {
+    ConfigError::Invalid { field, reason }
+}
+
+fn positive(field: &'static str, value: f32, allow_zero: bool) -> Result<(), ConfigError> {
+    if !value.is_finite() || value < 0. || (!allow_zero && value == 0.) {
+        let bound = if allow_zero {
+            "zero or more"
+        } else {
+            "more than zero"
+        };
+        return Err(invalid(field, format!("must be {bound} (got {value})")));
+    }
+    Ok(())
+}
+
+fn color(field: &'static str, rgb: [f32; 3]) -> Result<(), ConfigError> {
+    if rgb.iter().any(|c| !(0. ..=1.).contains(c)) {
+        return Err(invalid(
+            field,
+            format!("channels must be between 0 and 1 (got {rgb:?})"),
+        ));
+    }
+    Ok(())
+}
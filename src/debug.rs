@@ -0,0 +1,203 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    ecs::entity::Entities,
+    prelude::*,
+};
+
+use crate::{
+    bird::{Bird, DeathCause, LastDeath, BIRD_SIZE},
+    collision::{bird_pixels, pipe_rects, CollisionMasks, Hitbox},
+    config::FlappConfig,
+    obstacles::PipePair,
+    physics::PhysicsTransform,
+    rng::GameRng,
+    GameManager, GameState,
+};
+
+//debug overlay
+pub const DEBUG_KEY: KeyCode = KeyCode::F3;
+pub const DEBUG_TEXT_SIZE: f32 = 4.;
+pub const DEBUG_TEXT_PAD: f32 = 4.;
+pub const BIRD_HITBOX_COLOR: Color = Color::srgb(0., 1., 0.);
+pub const PIPE_COLOR: Color = Color::srgb(0.2, 0.4, 1.);
+//the part of a pipe forgiven by the mercy zone
+pub const MERCY_COLOR: Color = Color::srgb(1., 0.8, 0.);
+pub const CULPRIT_COLOR: Color = Color::srgb(1., 0., 0.);
+pub const DEBUG_TEXT_COLOR: Color = Color::WHITE;
+
+#[derive(Resource, Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+}
+
+#[derive(Component)]
+pub struct DebugText;
+
+//F3 shows hitboxes and a physics readout, the hitboxes are drawn where the simulation has them
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+            app.add_plugins(FrameTimeDiagnosticsPlugin);
+        }
+        app.init_resource::<DebugOverlay>().add_systems(
+            Update,
+            (
+                toggle_debug_overlay,
+                (draw_hitboxes, update_debug_text).run_if(debug_overlay_enabled),
+            )
+                .chain(),
+        );
+    }
+}
+
+fn debug_overlay_enabled(overlay: Res<DebugOverlay>) -> bool {
+    overlay.enabled
+}
+
+fn toggle_debug_overlay(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    config: Res<FlappConfig>,
+    mut overlay: ResMut<DebugOverlay>,
+    text_query: Query<Entity, With<DebugText>>,
+) {
+    if !keys.just_pressed(DEBUG_KEY) {
+        return;
+    }
+    overlay.enabled = !overlay.enabled;
+    if !overlay.enabled {
+        for entity in text_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: DEBUG_TEXT_SIZE * config.pixel_ratio,
+            ..Default::default()
+        },
+        TextColor(DEBUG_TEXT_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(DEBUG_TEXT_PAD * config.pixel_ratio),
+            right: Val::Px(DEBUG_TEXT_PAD * config.pixel_ratio),
+            ..Default::default()
+        },
+        DebugText,
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_hitboxes(
+    mut gizmos: Gizmos,
+    config: Res<FlappConfig>,
+    masks: Res<CollisionMasks>,
+    game_manager: Res<GameManager>,
+    state: Res<State<GameState>>,
+    last_death: Res<LastDeath>,
+    bird_query: Query<&PhysicsTransform, With<Bird>>,
+    pair_query: Query<(Entity, &PipePair, &PhysicsTransform)>,
+) {
+    //the pipes reach off forever, only the part on screen is drawn
+    let half_height = game_manager.window_dimensions.y / 2.;
+    let on_screen = |rect: Rect| Rect {
+        min: rect.min.max(Vec2::new(rect.min.x, -half_height)),
+        max: rect.max.min(Vec2::new(rect.max.x, half_height)),
+    };
+    let culprit = match (state.get(), last_death.0) {
+        (
+            GameState::GameOver,
+            Some(DeathCause::Pipe {
+                pair,
+                pipe_direction,
+            }),
+        ) => Some((pair, pipe_direction)),
+        _ => None,
+    };
+
+    for (entity, pair, transform) in pair_query.iter() {
+        let outer = pipe_rects(transform.translation, pair, config.pixel_ratio, 0.);
+        let inner = pipe_rects(
+            transform.translation,
+            pair,
+            config.pixel_ratio,
+            config.mercy_zone,
+        );
+        for ((outer, inner), pipe_direction) in outer.into_iter().zip(inner).zip([1., -1.]) {
+            let (outer, inner) = (on_screen(outer), on_screen(inner));
+            let color = match culprit == Some((entity, pipe_direction)) {
+                true => CULPRIT_COLOR,
+                false => PIPE_COLOR,
+            };
+            gizmos.rect_2d(outer.center(), outer.size(), MERCY_COLOR);
+            gizmos.rect_2d(inner.center(), inner.size(), color);
+        }
+    }
+
+    let Ok(bird) = bird_query.get_single() else {
+        return;
+    };
+    let radius = BIRD_SIZE.y * config.pixel_ratio / 2.;
+    match config.hitbox {
+        Hitbox::Circle => {
+            gizmos.circle_2d(bird.translation, radius, BIRD_HITBOX_COLOR);
+        }
+        Hitbox::Capsule => {
+            gizmos.primitive_2d(
+                &Capsule2d::new(radius, (BIRD_SIZE.x - BIRD_SIZE.y) * config.pixel_ratio),
+                //capsules stand upright, the bird's lies along its x axis
+                Isometry2d::new(bird.translation, Rot2::radians(bird.rotation - FRAC_PI_2)),
+                BIRD_HITBOX_COLOR,
+            );
+        }
+        Hitbox::PixelMask => {
+            for point in bird_pixels(bird, &config, &masks.bird) {
+                gizmos.rect_2d(
+                    Isometry2d::new(point, Rot2::radians(bird.rotation)),
+                    Vec2::splat(config.pixel_ratio),
+                    BIRD_HITBOX_COLOR,
+                );
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_debug_text(
+    diagnostics: Res<DiagnosticsStore>,
+    entities: &Entities,
+    rng: Res<GameRng>,
+    config: Res<FlappConfig>,
+    last_death: Res<LastDeath>,
+    bird_query: Query<(&Bird, &PhysicsTransform)>,
+    mut text_query: Query<&mut Text, With<DebugText>>,
+) {
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or_default();
+    let (velocity, rotation) = bird_query
+        .get_single()
+        .map(|(bird, transform)| (bird.velocity, transform.rotation.to_degrees()))
+        .unwrap_or_default();
+    let death = match last_death.0 {
+        Some(DeathCause::Ground) => "ground",
+        Some(DeathCause::Ceiling) => "ceiling",
+        Some(DeathCause::Pipe { .. }) => "pipe",
+        None => "-",
+    };
+    for mut text in text_query.iter_mut() {
+        text.0 = format!(
+            "fps {fps:.0}\nentities {}\nseed {}\nvelocity {velocity:.1}\nrotation {rotation:.1}\nhitbox {:?}\nmercy {}\ndeath {death}",
+            entities.len(),
+            rng.seed(),
+            config.hitbox,
+            config.mercy_zone,
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use bevy::{prelude::*, state::app::StatesPlugin, time::TimeUpdateStrategy};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bird::{Bird, FlapInput},
+    cli::Args,
+    config::{ConfigError, FlappConfig},
+    obstacles::PipePair,
+    physics::{PhysicsTick, PhysicsTransform},
+    rng::GameRng,
+    score::Score,
+    FlappCorePlugin, GameState,
+};
+
+//rewards handed out by `FlappEnv::step`
+pub const TICK_REWARD: f32 = 0.1;
+pub const PIPE_REWARD: f32 = 1.;
+pub const DEATH_REWARD: f32 = -1.;
+//pipe pairs ahead of the bird included in an observation
+pub const OBSERVED_PIPES: usize = 2;
+
+//what the agent does on a tick
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvAction {
+    Idle,
+    Flap,
+}
+
+//a pipe pair ahead of the bird, measured from the bird in world units
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct PipeGap {
+    //to the centre of the pair, positive ahead
+    pub dx: f32,
+    //to the centre of the gap, positive above
+    pub dy: f32,
+    pub gap_size: f32,
+}
+
+//what the agent gets to see after each tick
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Observation {
+    pub bird_y: f32,
+    pub bird_velocity: f32,
+    //the nearest pairs the bird hasn't passed yet, closest first
+    pub pipes: [PipeGap; OBSERVED_PIPES],
+}
+
+//the game as a step-by-step environment for training agents
+//drives the same systems as the game, so runs play out exactly like a recorded or headless run on the same seed
+pub struct FlappEnv {
+    app: App,
+    bird_query: QueryState<(&'static Bird, &'static PhysicsTransform)>,
+    pair_query: QueryState<(&'static PipePair, &'static PhysicsTransform)>,
+}
+
+impl FlappEnv {
+    pub fn new(config: FlappConfig) -> Result<Self, ConfigError> {
+        config.validate()?;
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin))
+            .insert_resource(config)
+            .insert_resource(Args::default())
+            .add_plugins(FlappCorePlugin);
+        //every update advances the simulation by exactly one physics tick
+        let timestep = app.world().resource::<Time<Fixed>>().timestep();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep));
+        app.world_mut()
+            .resource_mut::<Time<Virtual>>()
+            .set_max_delta(timestep);
+        app.finish();
+        app.cleanup();
+        app.update();
+
+        let bird_query = app.world_mut().query();
+        let pair_query = app.world_mut().query();
+        Ok(FlappEnv {
+            app,
+            bird_query,
+            pair_query,
+        })
+    }
+
+    //starts a new run on `seed`, the pipes come up the same way every time for the same seed
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        let world = self.app.world_mut();
+        world.resource_mut::<Args>().seed = Some(seed);
+        //state changes are applied on their own, so a reset doesn't cost a physics tick
+        for state in [GameState::Countdown, GameState::Playing] {
+            world.resource_mut::<NextState<GameState>>().set(state);
+            world.run_schedule(StateTransition);
+        }
+        self.observe()
+    }
+
+    //plays one physics tick, nothing happens once the run is over until the next `reset`
+    pub fn step(&mut self, action: EnvAction) -> (Observation, f32, bool) {
+        if self.done() {
+            return (self.observe(), 0., true);
+        }
+        let score = self.score();
+        self.app.world_mut().resource_mut::<FlapInput>().pending = action == EnvAction::Flap;
+        self.app.update();
+        //a death is only a pending state change after the tick, settle it now rather than on the next step
+        self.app.world_mut().run_schedule(StateTransition);
+
+        let done = self.done();
+        let reward = if done {
+            DEATH_REWARD
+        } else {
+            TICK_REWARD + (self.score() - score) as f32 * PIPE_REWARD
+        };
+        (self.observe(), reward, done)
+    }
+
+    pub fn observe(&mut self) -> Observation {
+        let world = self.app.world();
+        let Ok((bird, transform)) = self.bird_query.get_single(world) else {
+            return Observation::default();
+        };
+        observe(bird, transform, self.pair_query.iter(world))
+    }
+
+    //the run has ended, or never started
+    pub fn done(&self) -> bool {
+        *self.app.world().resource::<State<GameState>>().get() != GameState::Playing
+    }
+
+    pub fn score(&self) -> u32 {
+        self.app.world().resource::<Score>().value
+    }
+
+    //physics ticks played in the current run
+    pub fn tick(&self) -> u64 {
+        self.app.world().resource::<PhysicsTick>().0
+    }
+
+    //seed of the current run
+    pub fn seed(&self) -> u64 {
+        self.app.world().resource::<GameRng>().seed()
+    }
+
+    pub fn config(&self) -> &FlappConfig {
+        self.app.world().resource::<FlappConfig>()
+    }
+}
+
+//what a bird at `transform` sees of `pairs`, shared with anything else flying birds on observations
+pub fn observe<'a>(
+    bird: &Bird,
+    transform: &PhysicsTransform,
+    pairs: impl IntoIterator<Item = (&'a PipePair, &'a PhysicsTransform)>,
+) -> Observation {
+    let bird_position = transform.translation;
+    let mut ahead: Vec<PipeGap> = pairs
+        .into_iter()
+        .filter(|(pair, _)| !pair.passed)
+        .map(|(pair, pair_transform)| PipeGap {
+            dx: pair_transform.translation.x - bird_position.x,
+            dy: pair_transform.translation.y - bird_position.y,
+            gap_size: pair.gap_size,
+        })
+        .collect();
+    ahead.sort_by(|a, b| a.dx.total_cmp(&b.dx));
+
+    let mut pipes = [PipeGap::default(); OBSERVED_PIPES];
+    for (pipe, gap) in pipes.iter_mut().zip(ahead) {
+        *pipe = gap;
+    }
+    Observation {
+        bird_y: bird_position.y,
+        bird_velocity: bird.velocity,
+        pipes,
+    }
+}
@@ -0,0 +1,165 @@
+use bevy::prelude::*;
+
+use crate::{
+    bird::{fly, hit_ceiling},
+    config::FlappConfig,
+    physics::{
+        physics_transform, PhysicsSet, PhysicsTick, PhysicsTransform, PreviousPhysicsTransform,
+    },
+    replay::ReplayPlayback,
+    GameManager, GameState, SpriteImages,
+};
+
+//ghost
+pub const GHOST_TINT: Color = Color::srgba(0.6, 0.9, 1., 0.45);
+pub const GHOST_LABEL: &str = "best";
+pub const GHOST_LABEL_SIZE: f32 = 6.;
+pub const GHOST_LABEL_OFFSET: f32 = 9.;
+
+//a saved run flown next to the player, it never collides or scores
+#[derive(Component)]
+pub struct Ghost {
+    pub velocity: f32,
+    //the recorded run has ended, the ghost is hidden until the next run
+    pub finished: bool,
+}
+
+#[derive(Component)]
+pub struct GhostLabel;
+
+//the run the ghost flies
+#[derive(Resource)]
+pub struct GhostRun(pub ReplayPlayback);
+
+//only does anything when a `GhostRun` is inserted
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_ghost.run_if(resource_exists::<GhostRun>))
+            .add_systems(
+                OnEnter(GameState::Countdown),
+                reset_ghost.run_if(resource_exists::<GhostRun>),
+            )
+            .add_systems(
+                FixedUpdate,
+                update_ghost
+                    .in_set(PhysicsSet::Bird)
+                    .run_if(resource_exists::<GhostRun>),
+            )
+            .add_systems(Update, (show_ghost, follow_ghost));
+    }
+}
+
+fn spawn_ghost(
+    mut commands: Commands,
+    config: Res<FlappConfig>,
+    sprite_images: Res<SpriteImages>,
+    ghost_run: Res<GhostRun>,
+) {
+    commands.spawn((
+        Sprite {
+            image: sprite_images.bird.clone(),
+            color: GHOST_TINT,
+            ..Default::default()
+        },
+        //behind the bird
+        Transform::from_xyz(0., 0., -0.5).with_scale(Vec3::splat(config.pixel_ratio)),
+        physics_transform(Vec2::ZERO),
+        Ghost {
+            velocity: 0.,
+            finished: false,
+        },
+    ));
+    commands.spawn((
+        Text2d::new(format!("{GHOST_LABEL} {}", ghost_run.0.replay.score)),
+        TextFont {
+            font_size: GHOST_LABEL_SIZE,
+            ..Default::default()
+        },
+        TextColor(GHOST_TINT),
+        Transform::from_xyz(0., 0., -0.5).with_scale(Vec3::splat(config.pixel_ratio)),
+        GhostLabel,
+    ));
+}
+
+fn reset_ghost(
+    mut ghost_run: ResMut<GhostRun>,
+    mut ghost_query: Query<(
+        &mut Ghost,
+        &mut PhysicsTransform,
+        &mut PreviousPhysicsTransform,
+    )>,
+) {
+    ghost_run.0.restart();
+    for (mut ghost, mut physics, mut previous) in ghost_query.iter_mut() {
+        *physics = PhysicsTransform::default();
+        previous.0 = *physics;
+        ghost.velocity = 0.;
+        ghost.finished = false;
+    }
+}
+
+fn update_ghost(
+    time: Res<Time>,
+    tick: Res<PhysicsTick>,
+    config: Res<FlappConfig>,
+    game_manager: Res<GameManager>,
+    mut ghost_run: ResMut<GhostRun>,
+    mut ghost_query: Query<(&mut Ghost, &mut PhysicsTransform)>,
+) {
+    let Ok((mut ghost, mut physics)) = ghost_query.get_single_mut() else {
+        return;
+    };
+    //the recorded run died on its last tick
+    if tick.0 >= ghost_run.0.replay.ticks {
+        ghost.finished = true;
+        return;
+    }
+    let flap = ghost_run.0.flaps_on(tick.0);
+    fly(
+        &mut ghost.velocity,
+        &mut physics,
+        flap,
+        &config,
+        time.delta_secs(),
+    );
+    hit_ceiling(
+        &mut ghost.velocity,
+        &mut physics,
+        &config,
+        game_manager.window_dimensions.y,
+    );
+}
+
+//the label goes with the ghost
+fn show_ghost(
+    mut ghost_query: Query<(&Ghost, &mut Visibility), Changed<Ghost>>,
+    mut label_query: Query<&mut Visibility, (With<GhostLabel>, Without<Ghost>)>,
+) {
+    let Ok((ghost, mut ghost_visibility)) = ghost_query.get_single_mut() else {
+        return;
+    };
+    *ghost_visibility = match ghost.finished {
+        true => Visibility::Hidden,
+        false => Visibility::Inherited,
+    };
+    for mut visibility in label_query.iter_mut() {
+        *visibility = *ghost_visibility;
+    }
+}
+
+fn follow_ghost(
+    config: Res<FlappConfig>,
+    ghost_query: Query<&Transform, With<Ghost>>,
+    mut label_query: Query<&mut Transform, (With<GhostLabel>, Without<Ghost>)>,
+) {
+    let Ok(ghost_transform) = ghost_query.get_single() else {
+        return;
+    };
+    for mut transform in label_query.iter_mut() {
+        transform.translation.x = ghost_transform.translation.x;
+        transform.translation.y =
+            ghost_transform.translation.y + GHOST_LABEL_OFFSET * config.pixel_ratio;
+    }
+}
@@ -0,0 +1,70 @@
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+use crate::{physics::PhysicsTick, rng::GameRng, score::Score, FlappCorePlugin, GameState};
+
+//runs games back to back without a window as fast as the machine allows,
+//every update advances exactly one physics tick and each finished game is reported on stdout
+pub struct HeadlessPlugin {
+    pub runs: u64,
+}
+
+impl Default for HeadlessPlugin {
+    fn default() -> Self {
+        HeadlessPlugin { runs: 1 }
+    }
+}
+
+#[derive(Resource)]
+struct HeadlessRuns {
+    total: u64,
+    finished: u64,
+}
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(FlappCorePlugin);
+        let timestep = app.world().resource::<Time<Fixed>>().timestep();
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(timestep))
+            .insert_resource(HeadlessRuns {
+                total: self.runs,
+                finished: 0,
+            })
+            .add_systems(OnEnter(GameState::Title), start_run)
+            .add_systems(OnEnter(GameState::GameOver), report_run)
+            .add_systems(
+                Update,
+                skip_countdown.run_if(in_state(GameState::Countdown)),
+            );
+    }
+}
+
+pub(crate) fn start_run(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Countdown);
+}
+
+pub(crate) fn skip_countdown(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Playing);
+}
+
+fn report_run(
+    score: Res<Score>,
+    tick: Res<PhysicsTick>,
+    rng: Res<GameRng>,
+    mut runs: ResMut<HeadlessRuns>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    runs.finished += 1;
+    println!(
+        "run {} score {} ticks {} seed {}",
+        runs.finished,
+        score.value,
+        tick.0,
+        rng.seed()
+    );
+    if runs.finished >= runs.total {
+        exit.send(AppExit::Success);
+    } else {
+        next_state.set(GameState::Countdown);
+    }
+}
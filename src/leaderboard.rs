@@ -0,0 +1,147 @@
+use std::{
+    env, io,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    autopilot::Autopilot,
+    config::FlappConfig,
+    replay::ReplayPlayback,
+    rng::GameRng,
+    score::Score,
+    settings::{data_path, load_data, save_data},
+    GameState,
+};
+
+//leaderboard
+pub const LEADERBOARD_FILE: &str = "leaderboard.ron";
+pub const LEADERBOARD_SIZE: usize = 10;
+pub const DEFAULT_PLAYER_NAME: &str = "birb";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub score: u32,
+    //seconds since the unix epoch
+    pub date: u64,
+    pub seed: u64,
+    pub name: String,
+}
+
+//best runs on this machine, highest score first
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+    //where the last finished run landed, if it made the table
+    #[serde(skip)]
+    pub last_rank: Option<usize>,
+}
+
+impl Leaderboard {
+    pub fn path() -> Option<PathBuf> {
+        data_path(LEADERBOARD_FILE)
+    }
+
+    //saved table, or an empty one if there is none or it is broken
+    pub fn load() -> Self {
+        load_data(LEADERBOARD_FILE)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        save_data(LEADERBOARD_FILE, self)
+    }
+
+    //files the entry behind every run with at least the same score,
+    //returns its rank or `None` if it didn't make the table
+    pub fn submit(&mut self, entry: LeaderboardEntry) -> Option<usize> {
+        let rank = self
+            .entries
+            .iter()
+            .position(|other| other.score < entry.score)
+            .unwrap_or(self.entries.len());
+        self.last_rank = (rank < LEADERBOARD_SIZE).then_some(rank);
+        if self.last_rank.is_some() {
+            self.entries.insert(rank, entry);
+            self.entries.truncate(LEADERBOARD_SIZE);
+        }
+        self.last_rank
+    }
+
+    pub fn is_new_best(&self) -> bool {
+        self.last_rank == Some(0)
+    }
+}
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Leaderboard::load()).add_systems(
+            OnEnter(GameState::GameOver),
+            //played back runs were already filed when they were recorded
+            submit_score.run_if(not(resource_exists::<ReplayPlayback>)),
+        );
+    }
+}
+
+//file the run that just ended, pointless runs are not worth a spot
+pub fn submit_score(
+    mut leaderboard: ResMut<Leaderboard>,
+    config: Res<FlappConfig>,
+    score: Res<Score>,
+    rng: Res<GameRng>,
+    autopilot: Res<Autopilot>,
+) {
+    leaderboard.last_rank = None;
+    //the bot's runs say nothing about the player
+    if score.value == 0 || autopilot.used {
+        return;
+    }
+    let date = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let entry = LeaderboardEntry {
+        score: score.value,
+        date,
+        seed: rng.seed(),
+        name: player_name(&config),
+    };
+    if leaderboard.submit(entry).is_some() {
+        if let Err(err) = leaderboard.save() {
+            error!("could not save the leaderboard: {err}");
+        }
+    }
+}
+
+pub fn player_name(config: &FlappConfig) -> String {
+    config
+        .player_name
+        .clone()
+        .or_else(|| env::var("USER").ok())
+        .or_else(|| env::var("USERNAME").ok())
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| String::from(DEFAULT_PLAYER_NAME))
+}
+
+//`YYYY-MM-DD` in UTC
+pub fn format_date(date: u64) -> String {
+    //days to civil date, see howardhinnant.github.io/date_algorithms.html
+    let days = (date / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    format!("{year:04}-{month:02}-{day:02}")
+}
@@ -0,0 +1,209 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub mod actions;
+pub mod autopilot;
+pub mod bird;
+pub mod cli;
+pub mod collision;
+pub mod config;
+pub mod debug;
+pub mod env;
+pub mod ghost;
+pub mod headless;
+pub mod leaderboard;
+pub mod obstacles;
+pub mod physics;
+pub mod protocol;
+pub mod remote;
+pub mod replay;
+pub mod rng;
+pub mod score;
+pub mod settings;
+pub mod training;
+pub mod ui;
+pub mod view;
+
+use actions::ActionPlugin;
+use autopilot::{
+    restart_with_autopilot, show_autopilot_text, spawn_autopilot_text, toggle_autopilot,
+    AutopilotPlugin,
+};
+use bird::{attach_bird_sprites, read_flap_input, spawn_bird, Bird, BirdPlugin, FlapInput};
+use cli::Args;
+use collision::CollisionPlugin;
+use config::{ConfigPlugin, FlappConfig};
+use debug::DebugPlugin;
+use ghost::GhostPlugin;
+use leaderboard::LeaderboardPlugin;
+use obstacles::{
+    attach_obstacle_sprites, fit_obstacle_sprites, spawn_obstacles, ObstaclePlugin, PipePair,
+};
+use physics::{PhysicsPlugin, PhysicsTick, PhysicsTransform, PreviousPhysicsTransform};
+use replay::{ReplayPlayback, ReplayPlugin};
+use rng::{run_seed, GameRng};
+use score::{Score, ScorePlugin};
+use training::Training;
+use ui::UiPlugin;
+use view::ViewPlugin;
+
+//game general, the play field is this size whatever the window
+pub const WIN_X: f32 = 1280.;
+pub const WIN_Y: f32 = 720.;
+pub const WINDOW_TITLE: &str = "Flapp Birb";
+
+#[derive(States, Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum GameState {
+    #[default]
+    Title,
+    Countdown,
+    Playing,
+    Paused,
+    GameOver,
+    Leaderboard,
+    Options,
+}
+
+#[derive(Resource)]
+pub struct GameManager {
+    //size of the play field, fixed so the simulation doesn't depend on the window
+    pub window_dimensions: Vec2,
+}
+
+#[derive(Resource)]
+pub struct SpriteImages {
+    pub bird: Handle<Image>,
+    pub pipe: Handle<Image>,
+}
+
+impl FromWorld for SpriteImages {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        SpriteImages {
+            bird: asset_server.load("bird.png"),
+            pipe: asset_server.load("pipe.png"),
+        }
+    }
+}
+
+//the simulation alone: state machine, config, physics, bird, pipes and scoring
+//needs no window, renderer or assets, so it also runs under `MinimalPlugins`
+pub struct FlappCorePlugin;
+
+impl Plugin for FlappCorePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<GameState>()
+            .enable_state_scoped_entities::<GameState>()
+            .add_plugins((
+                ConfigPlugin,
+                PhysicsPlugin,
+                CollisionPlugin,
+                BirdPlugin,
+                ObstaclePlugin,
+                ScorePlugin,
+                ReplayPlugin,
+                AutopilotPlugin,
+            ))
+            .add_systems(Startup, setup_level)
+            .add_systems(OnEnter(GameState::Countdown), reset_game);
+    }
+}
+
+//the whole game, to be added next to `DefaultPlugins`
+//a `FlappConfig` inserted before this plugin is kept, otherwise it is loaded from `flapp.ron`
+pub struct FlappPlugin;
+
+impl Plugin for FlappPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            FlappCorePlugin,
+            ActionPlugin,
+            UiPlugin,
+            GhostPlugin,
+            LeaderboardPlugin,
+            DebugPlugin,
+            ViewPlugin,
+        ))
+        .init_resource::<SpriteImages>()
+        .add_systems(Startup, spawn_autopilot_text)
+        .add_systems(
+            Update,
+            (
+                read_flap_input.run_if(
+                    in_state(GameState::Playing).and(not(resource_exists::<ReplayPlayback>)),
+                ),
+                attach_bird_sprites,
+                attach_obstacle_sprites,
+                fit_obstacle_sprites.run_if(resource_changed::<FlappConfig>),
+                toggle_autopilot.run_if(not(in_state(GameState::Options))),
+                restart_with_autopilot
+                    .run_if(in_state(GameState::Title).or(in_state(GameState::GameOver))),
+                show_autopilot_text,
+            ),
+        );
+    }
+}
+
+fn setup_level(
+    mut commands: Commands,
+    config: Res<FlappConfig>,
+    args: Option<Res<Args>>,
+    training: Option<Res<Training>>,
+) {
+    let window_dimensions = Vec2::new(WIN_X, WIN_Y);
+    commands.insert_resource(GameManager { window_dimensions });
+
+    //score
+    commands.insert_resource(Score { value: 0 });
+
+    //bird, a training population brings its own
+    if training.is_none() {
+        spawn_bird(&mut commands, &config, 1.);
+    }
+
+    //obstacles
+    let mut rng = GameRng::new(run_seed(args.as_deref(), &config));
+    spawn_obstacles(&mut commands, &config, &mut rng, window_dimensions.x);
+    commands.insert_resource(rng);
+}
+
+//put the bird and pipes back at their starting positions for a new run
+#[allow(clippy::too_many_arguments)]
+fn reset_game(
+    mut commands: Commands,
+    mut bird_query: Query<
+        (
+            &mut Bird,
+            &mut PhysicsTransform,
+            &mut PreviousPhysicsTransform,
+        ),
+        Without<PipePair>,
+    >,
+    pair_query: Query<Entity, With<PipePair>>,
+    game_manager: Res<GameManager>,
+    config: Res<FlappConfig>,
+    args: Option<Res<Args>>,
+    mut rng: ResMut<GameRng>,
+    mut flap_input: ResMut<FlapInput>,
+    mut tick: ResMut<PhysicsTick>,
+    mut score: ResMut<Score>,
+) {
+    if let Ok((mut bird, mut physics, mut previous)) = bird_query.get_single_mut() {
+        *physics = PhysicsTransform::default();
+        previous.0 = *physics;
+        bird.velocity = 0.;
+    }
+    flap_input.pending = false;
+    tick.0 = 0;
+    score.value = 0;
+    for entity in pair_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    rng.reseed(run_seed(args.as_deref(), &config));
+    spawn_obstacles(
+        &mut commands,
+        &config,
+        &mut rng,
+        game_manager.window_dimensions.x,
+    );
+}
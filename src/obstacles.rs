@@ -0,0 +1,234 @@
+use bevy::{
+    prelude::*,
+    sprite::{Anchor, BorderRect, SliceScaleMode, SpriteImageMode, TextureSlicer},
+};
+use rand::Rng;
+
+use crate::{
+    config::FlappConfig,
+    physics::{physics_transform, PhysicsSet, PhysicsTransform, PreviousPhysicsTransform},
+    rng::GameRng,
+    GameManager, SpriteImages,
+};
+
+//obstacles and collision
+pub const OBSTACLE_AMOUNT: i32 = 8;
+pub const OBSTACLE_WIDTH: f32 = 18.;
+pub const OBSTACLE_HEIGHT: f32 = 144.;
+//pipe art, the cap is kept as is when the pipe is stretched to reach off screen
+pub const PIPE_CAP_HEIGHT: f32 = 5.;
+
+//one column of pipes, sitting at the centre of its gap with the top and bottom pipe as children
+#[derive(Component)]
+pub struct PipePair {
+    //distance between the ends of the two pipes
+    pub gap_size: f32,
+    //the bird is past this pair and has been given its point
+    pub passed: bool,
+}
+
+#[derive(Component)]
+pub struct Obstacle {
+    pub pipe_direction: f32,
+}
+
+pub struct ObstaclePlugin;
+
+impl Plugin for ObstaclePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            apply_config_to_obstacles.run_if(resource_changed::<FlappConfig>),
+        )
+        .add_systems(FixedUpdate, update_obstacles.in_set(PhysicsSet::Obstacles));
+    }
+}
+
+fn gap_size(config: &FlappConfig) -> f32 {
+    config.obstacle_gap * 2. * config.pixel_ratio
+}
+
+fn generate_offset(rand: &mut GameRng, config: &FlappConfig) -> f32 {
+    if config.obstacle_vertical_offset == 0. {
+        return 0.;
+    }
+    rand.gen_range(-config.obstacle_vertical_offset..config.obstacle_vertical_offset)
+        * config.pixel_ratio
+}
+
+pub fn spawn_obstacles(
+    commands: &mut Commands,
+    config: &FlappConfig,
+    rand: &mut GameRng,
+    window_width: f32,
+) {
+    for i in 0..OBSTACLE_AMOUNT {
+        let y_offset: f32 = generate_offset(rand, config);
+        let x_pos: f32 =
+            (window_width / 2.) + (config.obstacle_spacing * config.pixel_ratio * i as f32);
+        pipe_pair(Vec2::new(x_pos, y_offset), commands, config);
+    }
+}
+
+//spawn a pair of pipes around a gap centred on `translation`
+fn pipe_pair(translation: Vec2, commands: &mut Commands, config: &FlappConfig) {
+    let pair = PipePair {
+        gap_size: gap_size(config),
+        passed: false,
+    };
+    let top = pipe_transform(&pair, 1., config);
+    let bottom = pipe_transform(&pair, -1., config);
+    commands
+        .spawn((
+            Transform::from_translation(translation.extend(0.)),
+            Visibility::default(),
+            physics_transform(translation),
+            pair,
+        ))
+        .with_children(|pair| {
+            pair.spawn((top, Obstacle { pipe_direction: 1. }));
+            pair.spawn((
+                bottom,
+                Obstacle {
+                    pipe_direction: -1.,
+                },
+            ));
+        });
+}
+
+//where a pipe sits relative to its pair: at its end of the gap, the top pipe drawn upside down
+fn pipe_transform(pair: &PipePair, pipe_direction: f32, config: &FlappConfig) -> Transform {
+    Transform::from_xyz(0., pair.gap_size / 2. * pipe_direction, 0.).with_scale(Vec3::new(
+        config.pixel_ratio,
+        config.pixel_ratio * -pipe_direction,
+        config.pixel_ratio,
+    ))
+}
+
+//art pixels a pipe needs to reach past the screen edge from the furthest its gap can be moved
+pub fn pipe_length(config: &FlappConfig, window_height: f32) -> f32 {
+    let length = window_height / 2. / config.pixel_ratio + config.obstacle_vertical_offset
+        - config.obstacle_gap;
+    length.ceil().max(OBSTACLE_HEIGHT)
+}
+
+fn pipe_sprite(sprite_images: &SpriteImages, length: f32) -> Sprite {
+    Sprite {
+        image: sprite_images.pipe.clone(),
+        custom_size: Some(Vec2::new(OBSTACLE_WIDTH, length)),
+        image_mode: SpriteImageMode::Sliced(TextureSlicer {
+            border: BorderRect {
+                top: PIPE_CAP_HEIGHT,
+                ..BorderRect::ZERO
+            },
+            center_scale_mode: SliceScaleMode::Stretch,
+            sides_scale_mode: SliceScaleMode::Stretch,
+            max_corner_scale: 1.,
+        }),
+        //the cap end sits on the gap
+        anchor: Anchor::TopCenter,
+        ..Default::default()
+    }
+}
+
+pub(crate) fn attach_obstacle_sprites(
+    mut commands: Commands,
+    sprite_images: Res<SpriteImages>,
+    config: Res<FlappConfig>,
+    game_manager: Res<GameManager>,
+    obstacle_query: Query<Entity, (With<Obstacle>, Without<Sprite>)>,
+) {
+    let length = pipe_length(&config, game_manager.window_dimensions.y);
+    for entity in obstacle_query.iter() {
+        commands
+            .entity(entity)
+            .insert(pipe_sprite(&sprite_images, length));
+    }
+}
+
+//stretch the pipes again when the gap, its offset or the pixel ratio are edited
+pub(crate) fn fit_obstacle_sprites(
+    sprite_images: Res<SpriteImages>,
+    config: Res<FlappConfig>,
+    game_manager: Res<GameManager>,
+    mut sprite_query: Query<&mut Sprite, With<Obstacle>>,
+) {
+    let length = pipe_length(&config, game_manager.window_dimensions.y);
+    for mut sprite in sprite_query.iter_mut() {
+        *sprite = pipe_sprite(&sprite_images, length);
+    }
+}
+
+fn update_obstacles(
+    time: Res<Time>,
+    game_manager: Res<GameManager>,
+    config: Res<FlappConfig>,
+    mut rng: ResMut<GameRng>,
+    mut pair_query: Query<(
+        &mut PipePair,
+        &mut PhysicsTransform,
+        &mut PreviousPhysicsTransform,
+    )>,
+) {
+    for (mut pair, mut transform, mut previous) in pair_query.iter_mut() {
+        transform.translation.x -= time.delta_secs() * config.scroll_speed;
+        if transform.translation.x + OBSTACLE_WIDTH * config.pixel_ratio / 2.
+            < -game_manager.window_dimensions.x / 2.
+        {
+            transform.translation.x +=
+                OBSTACLE_AMOUNT as f32 * config.obstacle_spacing * config.pixel_ratio;
+            //only drawn when a pair is recycled, so the sequence of gaps depends on the seed alone
+            transform.translation.y = generate_offset(&mut rng, &config);
+            pair.passed = false;
+            //jump straight to the far end instead of sliding across the screen
+            previous.0 = *transform;
+        }
+    }
+}
+
+//move the pipes already on screen to match edited gap, spacing and pixel ratio
+fn apply_config_to_obstacles(
+    config: Res<FlappConfig>,
+    mut previous: Local<Option<FlappConfig>>,
+    mut pair_query: Query<(
+        &mut PipePair,
+        &mut PhysicsTransform,
+        &mut PreviousPhysicsTransform,
+        &Children,
+    )>,
+    mut pipe_query: Query<(&Obstacle, &mut Transform)>,
+) {
+    let Some(old) = previous.replace(config.clone()) else {
+        return;
+    };
+    if old.obstacle_gap == config.obstacle_gap
+        && old.obstacle_spacing == config.obstacle_spacing
+        && old.pixel_ratio == config.pixel_ratio
+    {
+        return;
+    }
+
+    //order the pairs from left to right to lay them out again
+    let mut columns: Vec<f32> = pair_query
+        .iter()
+        .map(|(_, physics, _, _)| physics.translation.x / old.pixel_ratio)
+        .collect();
+    columns.sort_by(f32::total_cmp);
+    let first_column = columns.first().copied().unwrap_or_default();
+
+    for (mut pair, mut physics, mut previous_physics, children) in pair_query.iter_mut() {
+        let x = physics.translation.x / old.pixel_ratio;
+        let column = columns.partition_point(|column| *column < x);
+
+        physics.translation.x =
+            (first_column + column as f32 * config.obstacle_spacing) * config.pixel_ratio;
+        physics.translation.y *= config.pixel_ratio / old.pixel_ratio;
+        previous_physics.0 = *physics;
+        pair.gap_size = gap_size(&config);
+        for &child in children.iter() {
+            if let Ok((obstacle, mut transform)) = pipe_query.get_mut(child) {
+                *transform = pipe_transform(&pair, obstacle.pipe_direction, &config);
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use bevy::{app::RunFixedMainLoopSystem, prelude::*};
+
+use crate::{config::FlappConfig, GameState};
+
+//each physics tick scrolls the pipes first, settles whether the bird flaps,
+//then moves the bird and checks it against them
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PhysicsSet {
+    Obstacles,
+    Input,
+    Bird,
+}
+
+//where the simulation has an entity, `Transform` only follows it for drawing
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct PhysicsTransform {
+    pub translation: Vec2,
+    //radians around z
+    pub rotation: f32,
+}
+
+//physics ticks since the current run started
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PhysicsTick(pub u64);
+
+//set by the tick that ends the run, the ticks left in the frame before the state changes don't play
+#[derive(Resource, Default)]
+pub struct RunOver(pub bool);
+
+//state at the start of the current tick, blended with `PhysicsTransform` between ticks
+#[derive(Component, Clone, Copy, Debug, Default)]
+pub struct PreviousPhysicsTransform(pub PhysicsTransform);
+
+impl PhysicsTransform {
+    pub fn from_translation(translation: Vec2) -> Self {
+        PhysicsTransform {
+            translation,
+            rotation: 0.,
+        }
+    }
+}
+
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        let tick_rate = app.world().resource::<FlappConfig>().tick_rate;
+        app.insert_resource(Time::<Fixed>::from_hz(tick_rate as f64))
+            .init_resource::<PhysicsTick>()
+            .init_resource::<RunOver>()
+            .configure_sets(
+                FixedUpdate,
+                (PhysicsSet::Obstacles, PhysicsSet::Input, PhysicsSet::Bird)
+                    .chain()
+                    .run_if(ticking),
+            )
+            .add_systems(FixedFirst, remember_physics_transforms)
+            .add_systems(
+                FixedUpdate,
+                count_tick.after(PhysicsSet::Bird).run_if(ticking),
+            )
+            .add_systems(OnExit(GameState::Playing), restart_ticking)
+            .add_systems(
+                RunFixedMainLoop,
+                interpolate_transforms.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+            )
+            .add_systems(
+                Update,
+                apply_tick_rate.run_if(resource_changed::<FlappConfig>),
+            );
+    }
+}
+
+//spawn helper, starts without anything to blend from
+pub fn physics_transform(translation: Vec2) -> (PhysicsTransform, PreviousPhysicsTransform) {
+    let physics = PhysicsTransform::from_translation(translation);
+    (physics, PreviousPhysicsTransform(physics))
+}
+
+fn remember_physics_transforms(
+    mut query: Query<(&PhysicsTransform, &mut PreviousPhysicsTransform)>,
+) {
+    for (physics, mut previous) in query.iter_mut() {
+        previous.0 = *physics;
+    }
+}
+
+//the simulation moves while playing, up to the tick that ends the run
+pub fn ticking(state: Res<State<GameState>>, run_over: Res<RunOver>) -> bool {
+    *state.get() == GameState::Playing && !run_over.0
+}
+
+//a state change asked for during a tick ends the run there, however many ticks the frame has left
+fn count_tick(
+    mut tick: ResMut<PhysicsTick>,
+    mut run_over: ResMut<RunOver>,
+    next_state: Res<NextState<GameState>>,
+) {
+    tick.0 += 1;
+    run_over.0 |= matches!(*next_state, NextState::Pending(_));
+}
+
+fn restart_ticking(mut run_over: ResMut<RunOver>) {
+    run_over.0 = false;
+}
+
+fn interpolate_transforms(
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&PhysicsTransform, &PreviousPhysicsTransform, &mut Transform)>,
+) {
+    let alpha = fixed_time.overstep_fraction();
+    for (physics, previous, mut transform) in query.iter_mut() {
+        let translation = previous.0.translation.lerp(physics.translation, alpha);
+        transform.translation.x = translation.x;
+        transform.translation.y = translation.y;
+        transform.rotation = Quat::from_rotation_z(
+            previous.0.rotation + (physics.rotation - previous.0.rotation) * alpha,
+        );
+    }
+}
+
+fn apply_tick_rate(config: Res<FlappConfig>, mut fixed_time: ResMut<Time<Fixed>>) {
+    fixed_time.set_timestep_hz(config.tick_rate as f64);
+}
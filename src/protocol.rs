@@ -0,0 +1,89 @@
+use std::io::{self, BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cli::Args,
+    env::{EnvAction, FlappEnv, Observation},
+    rng::run_seed,
+};
+
+//one line of input, e.g. `{"cmd":"reset","seed":3}` or `{"cmd":"step","flap":true}`
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Command {
+    //without a seed the run is seeded like any other, from `--seed`, the config or a fresh one
+    Reset {
+        #[serde(default)]
+        seed: Option<u64>,
+    },
+    Step {
+        #[serde(default)]
+        flap: bool,
+    },
+}
+
+//one line of output per command
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Reply {
+    State {
+        observation: Observation,
+        reward: f32,
+        done: bool,
+        score: u32,
+        tick: u64,
+        seed: u64,
+    },
+    //the line couldn't be read as a command, the run is left as it was
+    Error {
+        error: String,
+    },
+}
+
+pub fn handle(env: &mut FlappEnv, args: &Args, command: Command) -> Reply {
+    let (observation, reward, done) = match command {
+        Command::Reset { seed } => {
+            let seed = seed.unwrap_or_else(|| run_seed(Some(args), env.config()));
+            (env.reset(seed), 0., env.done())
+        }
+        Command::Step { flap } => env.step(if flap {
+            EnvAction::Flap
+        } else {
+            EnvAction::Idle
+        }),
+    };
+    Reply::State {
+        observation,
+        reward,
+        done,
+        score: env.score(),
+        tick: env.tick(),
+        seed: env.seed(),
+    }
+}
+
+//answers each line of `input` on `output` until `input` runs out, the game only moves when told to
+pub fn serve(
+    env: &mut FlappEnv,
+    args: &Args,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str(&line) {
+            Ok(command) => handle(env, args, command),
+            Err(err) => Reply::Error {
+                error: err.to_string(),
+            },
+        };
+        serde_json::to_writer(&mut output, &reply)?;
+        output.write_all(b"\n")?;
+        output.flush()?;
+    }
+    Ok(())
+}
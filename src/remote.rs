@@ -0,0 +1,425 @@
+use std::{
+    fmt, fs,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener},
+    path::PathBuf,
+    str::FromStr,
+    sync::{
+        mpsc::{channel, sync_channel, Receiver, Sender, SyncSender, TrySendError},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bird::{Bird, FlapInput},
+    obstacles::PipePair,
+    physics::{PhysicsTick, PhysicsTransform},
+    replay::ReplayPlayback,
+    score::Score,
+    GameState,
+};
+
+//remote control
+pub const UNIX_PREFIX: &str = "unix:";
+pub const REMOTE_HOST: [u8; 4] = [127, 0, 0, 1];
+//lines a client may fall behind by before it is cut off
+pub const REMOTE_QUEUE: usize = 1024;
+
+//where the game listens, `<port>`, `<ip>:<port>` on the loopback interface or `unix:<path>`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RemoteAddress {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl FromStr for RemoteAddress {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if let Some(path) = text.strip_prefix(UNIX_PREFIX) {
+            return Ok(RemoteAddress::Unix(PathBuf::from(path)));
+        }
+        let address = match text.parse::<u16>() {
+            Ok(port) => SocketAddr::from((REMOTE_HOST, port)),
+            Err(_) => text
+                .parse::<SocketAddr>()
+                .map_err(|_| format!("`{text}` is neither a port, an address nor `unix:<path>`"))?,
+        };
+        //anyone who can connect can play, so only this machine may
+        if !address.ip().is_loopback() {
+            return Err(format!("`{text}` isn't on localhost"));
+        }
+        Ok(RemoteAddress::Tcp(address))
+    }
+}
+
+impl fmt::Display for RemoteAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteAddress::Tcp(address) => write!(f, "{address}"),
+            RemoteAddress::Unix(path) => write!(f, "{UNIX_PREFIX}{}", path.display()),
+        }
+    }
+}
+
+//one line from a client, e.g. `{"cmd":"subscribe"}` or `{"cmd":"flap"}`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum RemoteCommand {
+    //receive the state after every physics tick
+    Subscribe,
+    Unsubscribe,
+    //receive the state once, right away
+    State,
+    //same as pressing the flap button, only while playing
+    Flap,
+    //same as pressing confirm on the title or game over screen
+    Start,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BirdState {
+    pub translation: Vec2,
+    //radians around z
+    pub rotation: f32,
+    pub velocity: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PipeState {
+    //centre of the gap
+    pub translation: Vec2,
+    pub gap_size: f32,
+    pub passed: bool,
+}
+
+//what subscribers get every tick, in world units
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TickState {
+    pub tick: u64,
+    pub state: GameState,
+    pub score: u32,
+    pub bird: Option<BirdState>,
+    //left to right
+    pub pipes: Vec<PipeState>,
+}
+
+#[derive(Serialize)]
+struct RemoteError {
+    error: String,
+}
+
+struct Connection {
+    id: u64,
+    //lines for the client, written out by its own thread so a slow client never holds up the game
+    lines: SyncSender<String>,
+    subscribed: bool,
+    //shuts the socket, which ends both of the client's threads
+    close: Box<dyn Fn() + Send>,
+}
+
+impl Connection {
+    //whether the client is still there to take more lines
+    fn push(&self, line: &str) -> bool {
+        match self.lines.try_send(line.to_owned()) {
+            Ok(()) => true,
+            //a client that stopped reading would otherwise pile up lines without end
+            Err(TrySendError::Full(_)) => {
+                (self.close)();
+                false
+            }
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+//socket the game is controlled through, accepting any number of clients
+#[derive(Resource)]
+pub struct RemoteServer {
+    address: RemoteAddress,
+    commands: Mutex<Receiver<(u64, RemoteCommand)>>,
+    connections: Arc<Mutex<Vec<Connection>>>,
+}
+
+impl RemoteServer {
+    pub fn bind(address: &RemoteAddress) -> io::Result<Self> {
+        let (sender, commands) = channel();
+        let connections = Arc::new(Mutex::new(Vec::new()));
+        let address = match address {
+            RemoteAddress::Tcp(address) => {
+                let listener = TcpListener::bind(address)?;
+                let bound = RemoteAddress::Tcp(listener.local_addr()?);
+                let connections = connections.clone();
+                thread::spawn(move || {
+                    for (id, stream) in (0..).zip(listener.incoming()) {
+                        let Ok(stream) = stream else { continue };
+                        let _ = stream.set_nodelay(true);
+                        let (Ok(writer), Ok(closer)) = (stream.try_clone(), stream.try_clone())
+                        else {
+                            continue;
+                        };
+                        let close = move || {
+                            let _ = closer.shutdown(Shutdown::Both);
+                        };
+                        connect(id, stream, writer, close, &sender, &connections);
+                    }
+                });
+                bound
+            }
+            #[cfg(unix)]
+            RemoteAddress::Unix(path) => {
+                use std::os::unix::{
+                    fs::FileTypeExt,
+                    net::{UnixListener, UnixStream},
+                };
+                //left behind by a game that didn't exit cleanly, nothing answers on it any more
+                let stale = fs::symlink_metadata(path)
+                    .is_ok_and(|metadata| metadata.file_type().is_socket())
+                    && UnixStream::connect(path).is_err();
+                if stale {
+                    fs::remove_file(path)?;
+                }
+                let listener = UnixListener::bind(path)?;
+                let connections = connections.clone();
+                thread::spawn(move || {
+                    for (id, stream) in (0..).zip(listener.incoming()) {
+                        let Ok(stream) = stream else { continue };
+                        let (Ok(writer), Ok(closer)) = (stream.try_clone(), stream.try_clone())
+                        else {
+                            continue;
+                        };
+                        let close = move || {
+                            let _ = closer.shutdown(Shutdown::Both);
+                        };
+                        connect(id, stream, writer, close, &sender, &connections);
+                    }
+                });
+                RemoteAddress::Unix(path.clone())
+            }
+            #[cfg(not(unix))]
+            RemoteAddress::Unix(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "unix sockets aren't available on this platform",
+                ))
+            }
+        };
+        Ok(RemoteServer {
+            address,
+            commands: Mutex::new(commands),
+            connections,
+        })
+    }
+
+    //where clients reach the server, with the port picked by the system when asked for port 0
+    pub fn address(&self) -> &RemoteAddress {
+        &self.address
+    }
+
+    fn send(&self, id: u64, line: &str) {
+        self.connections
+            .lock()
+            .unwrap()
+            .retain(|connection| connection.id != id || connection.push(line));
+    }
+
+    fn broadcast(&self, line: &str) {
+        self.connections
+            .lock()
+            .unwrap()
+            .retain(|connection| !connection.subscribed || connection.push(line));
+    }
+
+    fn set_subscribed(&self, id: u64, subscribed: bool) {
+        let mut connections = self.connections.lock().unwrap();
+        if let Some(connection) = connections
+            .iter_mut()
+            .find(|connection| connection.id == id)
+        {
+            connection.subscribed = subscribed;
+        }
+    }
+}
+
+impl Drop for RemoteServer {
+    //leaves no stale socket file behind to block the next game
+    fn drop(&mut self) {
+        if let RemoteAddress::Unix(path) = &self.address {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+//starts the threads serving one client: one reads its commands, one writes what the game sends it
+fn connect(
+    id: u64,
+    reader: impl Read + Send + 'static,
+    mut writer: impl Write + Send + 'static,
+    close: impl Fn() + Send + 'static,
+    commands: &Sender<(u64, RemoteCommand)>,
+    connections: &Arc<Mutex<Vec<Connection>>>,
+) {
+    let (lines, outgoing) = sync_channel::<String>(REMOTE_QUEUE);
+    connections.lock().unwrap().push(Connection {
+        id,
+        lines: lines.clone(),
+        subscribed: false,
+        close: Box::new(close),
+    });
+
+    thread::spawn(move || {
+        for line in outgoing {
+            if writer
+                .write_all(line.as_bytes())
+                .and_then(|()| writer.write_all(b"\n"))
+                .and_then(|()| writer.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    let commands = commands.clone();
+    let connections = connections.clone();
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else { break };
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(command) => {
+                    if commands.send((id, command)).is_err() {
+                        break;
+                    }
+                }
+                Err(err) => {
+                    let error = RemoteError {
+                        error: err.to_string(),
+                    };
+                    let _ = lines.try_send(serde_json::to_string(&error).unwrap_or_default());
+                }
+            }
+        }
+        //dropping the last sender ends the writing thread
+        connections
+            .lock()
+            .unwrap()
+            .retain(|connection| connection.id != id);
+    });
+}
+
+//everything a `TickState` is made of
+#[derive(SystemParam)]
+pub struct GameSnapshot<'w, 's> {
+    bird_query: Query<'w, 's, (&'static Bird, &'static PhysicsTransform)>,
+    pair_query: Query<'w, 's, (&'static PipePair, &'static PhysicsTransform)>,
+    tick: Res<'w, PhysicsTick>,
+    state: Res<'w, State<GameState>>,
+    score: Res<'w, Score>,
+}
+
+impl GameSnapshot<'_, '_> {
+    pub fn tick_state(&self) -> TickState {
+        let bird = self
+            .bird_query
+            .get_single()
+            .ok()
+            .map(|(bird, transform)| BirdState {
+                translation: transform.translation,
+                rotation: transform.rotation,
+                velocity: bird.velocity,
+            });
+        let mut pipes: Vec<PipeState> = self
+            .pair_query
+            .iter()
+            .map(|(pair, transform)| PipeState {
+                translation: transform.translation,
+                gap_size: pair.gap_size,
+                passed: pair.passed,
+            })
+            .collect();
+        pipes.sort_by(|a, b| a.translation.x.total_cmp(&b.translation.x));
+        TickState {
+            tick: self.tick.0,
+            state: *self.state.get(),
+            score: self.score.value,
+            bird,
+            pipes,
+        }
+    }
+}
+
+//takes commands from the `RemoteServer` resource, which has to be inserted before this plugin
+pub struct RemotePlugin;
+
+impl Plugin for RemotePlugin {
+    fn build(&self, app: &mut App) {
+        if let Some(server) = app.world().get_resource::<RemoteServer>() {
+            info!("remote control listening on {}", server.address());
+        }
+        app.add_systems(
+            Update,
+            handle_remote_commands.run_if(resource_exists::<RemoteServer>),
+        )
+        .add_systems(
+            FixedPostUpdate,
+            //once per tick played, including the one that ends the run
+            broadcast_tick_state.run_if(
+                resource_exists::<RemoteServer>
+                    .and(in_state(GameState::Playing))
+                    .and(resource_changed::<PhysicsTick>),
+            ),
+        )
+        .add_systems(
+            OnEnter(GameState::GameOver),
+            broadcast_tick_state.run_if(resource_exists::<RemoteServer>),
+        );
+    }
+}
+
+fn handle_remote_commands(
+    server: Res<RemoteServer>,
+    snapshot: GameSnapshot,
+    playback: Option<Res<ReplayPlayback>>,
+    mut flap_input: ResMut<FlapInput>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let commands: Vec<_> = server.commands.lock().unwrap().try_iter().collect();
+    for (id, command) in commands {
+        match command {
+            RemoteCommand::Subscribe => server.set_subscribed(id, true),
+            RemoteCommand::Unsubscribe => server.set_subscribed(id, false),
+            RemoteCommand::State => {
+                if let Ok(line) = serde_json::to_string(&snapshot.tick_state()) {
+                    server.send(id, &line);
+                }
+            }
+            //a replay decides every flap itself
+            RemoteCommand::Flap => {
+                if *snapshot.state.get() == GameState::Playing && playback.is_none() {
+                    flap_input.pending = true;
+                }
+            }
+            RemoteCommand::Start => {
+                if matches!(
+                    snapshot.state.get(),
+                    GameState::Title | GameState::GameOver | GameState::Leaderboard
+                ) {
+                    next_state.set(GameState::Countdown);
+                }
+            }
+        }
+    }
+}
+
+fn broadcast_tick_state(server: Res<RemoteServer>, snapshot: GameSnapshot) {
+    if let Ok(line) = serde_json::to_string(&snapshot.tick_state()) {
+        server.broadcast(&line);
+    }
+}
@@ -0,0 +1,220 @@
+use std::{fmt, fs, io, path::Path, path::PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bird::FlapInput,
+    config::FlappConfig,
+    physics::{PhysicsSet, PhysicsTick},
+    rng::GameRng,
+    score::Score,
+    GameState,
+};
+
+pub const REPLAY_VERSION: u32 = 1;
+
+//everything needed to play a run back: the seed, the tuning it was played with,
+//and the physics ticks on which the bird flapped
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub version: u32,
+    pub seed: u64,
+    pub config_hash: u64,
+    pub flaps: Vec<u64>,
+    pub score: u32,
+    pub ticks: u64,
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Write(ron::Error),
+    Version(u32),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "could not access replay: {err}"),
+            ReplayError::Parse(err) => write!(f, "could not parse replay: {err}"),
+            ReplayError::Write(err) => write!(f, "could not write replay: {err}"),
+            ReplayError::Version(version) => write!(
+                f,
+                "replay version {version} is not supported (expected {REPLAY_VERSION})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, ReplayError> {
+        let text = fs::read_to_string(path).map_err(ReplayError::Io)?;
+        let replay: Replay = ron::from_str(&text).map_err(ReplayError::Parse)?;
+        if replay.version != REPLAY_VERSION {
+            return Err(ReplayError::Version(replay.version));
+        }
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), ReplayError> {
+        let text = ron::to_string(self).map_err(ReplayError::Write)?;
+        fs::write(path, text).map_err(ReplayError::Io)
+    }
+}
+
+//stable fingerprint of the tuning a run was played with (FNV-1a over its RON form),
+//only what moves the bird and the pipes, colours and names don't change how a run plays back
+pub fn config_hash(config: &FlappConfig) -> u64 {
+    let simulation = (
+        config.gravity,
+        config.flap_force,
+        config.scroll_speed,
+        config.obstacle_spacing,
+        config.obstacle_gap,
+        config.obstacle_vertical_offset,
+        config.mercy_zone,
+        config.pixel_ratio,
+        config.hitbox,
+        config.ceiling,
+        config.tick_rate,
+    );
+    let text = ron::to_string(&simulation).unwrap_or_default();
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+//flaps of the current run, kept for every run so it can be saved when it ends
+#[derive(Resource, Default)]
+pub struct ReplayRecorder {
+    pub flaps: Vec<u64>,
+}
+
+//file every finished run is written to
+#[derive(Resource)]
+pub struct ReplayOutput(pub PathBuf);
+
+//replaces live input with the flaps of a recorded run
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    pub replay: Replay,
+    next_flap: usize,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayback {
+            replay,
+            next_flap: 0,
+        }
+    }
+
+    pub fn restart(&mut self) {
+        self.next_flap = 0;
+    }
+
+    //whether the recording flapped on `tick`
+    pub fn flaps_on(&mut self, tick: u64) -> bool {
+        let mut flapped = false;
+        while let Some(&flap) = self.replay.flaps.get(self.next_flap) {
+            if flap > tick {
+                break;
+            }
+            flapped |= flap == tick;
+            self.next_flap += 1;
+        }
+        flapped
+    }
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ReplayRecorder>()
+            .add_systems(Startup, check_replay_config)
+            .add_systems(OnEnter(GameState::Countdown), restart_replay)
+            .add_systems(OnEnter(GameState::GameOver), finish_replay)
+            .add_systems(
+                FixedUpdate,
+                (
+                    play_back_flaps.run_if(resource_exists::<ReplayPlayback>),
+                    record_flaps,
+                )
+                    .chain()
+                    .in_set(PhysicsSet::Input),
+            );
+    }
+}
+
+fn check_replay_config(config: Res<FlappConfig>, playback: Option<Res<ReplayPlayback>>) {
+    if let Some(playback) = playback {
+        if playback.replay.config_hash != config_hash(&config) {
+            warn!("replay was recorded with a different config, it may not play back the same");
+        }
+    }
+}
+
+fn restart_replay(mut recorder: ResMut<ReplayRecorder>, playback: Option<ResMut<ReplayPlayback>>) {
+    recorder.flaps.clear();
+    if let Some(mut playback) = playback {
+        playback.restart();
+    }
+}
+
+fn play_back_flaps(
+    tick: Res<PhysicsTick>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut flap_input: ResMut<FlapInput>,
+) {
+    flap_input.pending = playback.flaps_on(tick.0);
+}
+
+pub(crate) fn record_flaps(
+    tick: Res<PhysicsTick>,
+    flap_input: Res<FlapInput>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    if flap_input.pending {
+        recorder.flaps.push(tick.0);
+    }
+}
+
+fn finish_replay(
+    config: Res<FlappConfig>,
+    rng: Res<GameRng>,
+    score: Res<Score>,
+    tick: Res<PhysicsTick>,
+    recorder: Res<ReplayRecorder>,
+    output: Option<Res<ReplayOutput>>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    if let Some(playback) = playback {
+        if playback.replay.score == score.value && playback.replay.ticks == tick.0 {
+            info!("replay verified, score {}", score.value);
+        } else {
+            warn!(
+                "replay diverged: recorded score {} in {} ticks, played back score {} in {} ticks",
+                playback.replay.score, playback.replay.ticks, score.value, tick.0
+            );
+        }
+    }
+    if let Some(output) = output {
+        let replay = Replay {
+            version: REPLAY_VERSION,
+            seed: rng.seed(),
+            config_hash: config_hash(&config),
+            flaps: recorder.flaps.clone(),
+            score: score.value,
+            ticks: tick.0,
+        };
+        match replay.save(&output.0) {
+            Ok(()) => info!("saved replay to {}", output.0.display()),
+            Err(err) => error!("{}: {err}", output.0.display()),
+        }
+    }
+}
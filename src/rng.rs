@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use rand::{thread_rng, Rng, RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{cli::Args, config::FlappConfig};
+
+//the one random source all pipe placement draws from, reseeded at the start of every run
+#[derive(Resource)]
+pub struct GameRng {
+    seed: u64,
+    rng: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        GameRng {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn reseed(&mut self, seed: u64) {
+        *self = GameRng::new(seed);
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+//seed for the next run: `--seed` wins over the config, otherwise a fresh one each run
+pub fn run_seed(args: Option<&Args>, config: &FlappConfig) -> u64 {
+    args.and_then(|args| args.seed)
+        .or(config.seed)
+        .unwrap_or_else(|| thread_rng().gen::<u32>() as u64)
+}
+
+pub fn seed_text(seed: u64) -> String {
+    format!("Seed: {seed}")
+}
@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+
+use crate::{
+    config::{srgb, FlappConfig},
+    GameState,
+};
+
+//score display
+pub const SCORE_DISPLAY: &str = "Score: ";
+pub const SCORE_TEXT_SIZE: f32 = 10.;
+pub const SCORE_POS_PAD_X: f32 = 30.;
+pub const SCORE_POS_PAD_Y: f32 = 15.;
+
+#[derive(Resource)]
+pub struct Score {
+    pub value: u32,
+}
+
+#[derive(Component)]
+pub struct ScoreText;
+
+pub struct ScorePlugin;
+
+impl Plugin for ScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            update_score_text
+                .run_if(in_state(GameState::Countdown).or(in_state(GameState::Playing))),
+        );
+    }
+}
+
+pub fn spawn_score_text(commands: &mut Commands, config: &FlappConfig, window_dimensions: Vec2) {
+    //glyphs are drawn at art size and scaled up like the sprites, so they land on whole play field pixels
+    commands.spawn((
+        Text2d::new(SCORE_DISPLAY),
+        TextFont {
+            font_size: SCORE_TEXT_SIZE,
+            ..Default::default()
+        },
+        TextColor(srgb(config.score_text_color)),
+        Transform::from_xyz(
+            -window_dimensions.x / 2. + (SCORE_POS_PAD_X * config.pixel_ratio),
+            window_dimensions.y / 2. - (SCORE_POS_PAD_Y * config.pixel_ratio),
+            1.,
+        )
+        .with_scale(Vec3::splat(config.pixel_ratio)),
+        ScoreText,
+    ));
+}
+
+pub fn score_text(score: u32) -> String {
+    String::from(SCORE_DISPLAY) + format!("{}", score).as_str()
+}
+
+fn update_score_text(score: ResMut<Score>, mut query: Query<&mut Text2d, With<ScoreText>>) {
+    if let Ok(mut text) = query.get_single_mut() {
+        text.0 = score_text(score.value);
+    }
+}
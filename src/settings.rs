@@ -0,0 +1,74 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::actions::Bindings;
+
+//settings
+pub const SETTINGS_FILE: &str = "settings.ron";
+//folder inside the platform data directory
+pub const DATA_FOLDER: &str = "flapp";
+
+//where a file kept between sessions lives, none on platforms without a data directory
+pub fn data_path(file: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(DATA_FOLDER).join(file))
+}
+
+//a file kept between sessions, the default if there is none or it is broken
+pub fn load_data<T: DeserializeOwned + Default>(file: &str) -> T {
+    let Some(path) = data_path(file) else {
+        return T::default();
+    };
+    match fs::read_to_string(&path) {
+        Ok(text) => ron::from_str(&text).unwrap_or_else(|err| {
+            error!("{}: {err}, starting from the defaults", path.display());
+            T::default()
+        }),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => T::default(),
+        Err(err) => {
+            error!("{}: {err}, starting from the defaults", path.display());
+            T::default()
+        }
+    }
+}
+
+pub fn save_data<T: Serialize>(file: &str, value: &T) -> io::Result<()> {
+    let Some(path) = data_path(file) else {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no data directory on this platform",
+        ));
+    };
+    save_ron(&path, value)
+}
+
+//writes `value` to `path`, making its folder if needed
+pub fn save_ron<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let text = ron::ser::to_string_pretty(value, Default::default()).map_err(io::Error::other)?;
+    fs::write(path, text)
+}
+
+//player preferences changed from inside the game, unlike `FlappConfig` which is edited by hand
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub bindings: Bindings,
+}
+
+impl Settings {
+    //saved settings, or the defaults if there are none or they are broken
+    pub fn load() -> Self {
+        load_data(SETTINGS_FILE)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        save_data(SETTINGS_FILE, self)
+    }
+}
@@ -0,0 +1,454 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bird::{fly, passes_pair, tick_death, Bird},
+    cli::Args,
+    collision::CollisionMasks,
+    config::FlappConfig,
+    env::{observe, Observation, OBSERVED_PIPES},
+    headless::{skip_countdown, start_run, HeadlessPlugin},
+    obstacles::PipePair,
+    physics::{physics_transform, PhysicsSet, PhysicsTransform, PreviousPhysicsTransform, RunOver},
+    score::Score,
+    settings::{data_path, save_ron},
+    GameManager, GameState, WIN_X, WIN_Y,
+};
+
+//network
+pub const INPUTS: usize = 2 + 2 * OBSERVED_PIPES;
+pub const HIDDEN: usize = 6;
+pub const GENOME_SIZE: usize = (INPUTS + 1) * HIDDEN + HIDDEN + 1;
+
+//evolution
+pub const DEFAULT_POPULATION: usize = 50;
+//tournaments and breeding need at least two parents to pick from
+pub const MIN_POPULATION: usize = 2;
+//share of each generation carried over untouched
+pub const ELITE_SHARE: f32 = 0.1;
+pub const TOURNAMENT_SIZE: usize = 3;
+//chance for each weight of a child to be nudged, and by up to how much
+pub const MUTATION_RATE: f32 = 0.1;
+pub const MUTATION_SIZE: f32 = 0.5;
+pub const INITIAL_WEIGHT: f32 = 1.;
+//fitness is ticks survived, plus this for every pipe passed
+pub const PIPE_FITNESS: f32 = 100.;
+//taken off per gap size the bird was away from the gap centre when it died
+pub const MISS_PENALTY: f32 = 10.;
+//a generation ends once its survivors pass this many pipes, a bird that can't crash would keep it going forever
+pub const GENERATION_SCORE: u32 = 100;
+pub const BEST_GENOME_FILE: &str = "best_genome.ron";
+
+//training display
+pub const TRAINING_TEXT_SIZE: f32 = 4.;
+pub const TRAINING_TEXT_PAD: f32 = 4.;
+pub const TRAINING_TEXT_COLOR: Color = Color::WHITE;
+
+//weights of a small network deciding when to flap: inputs, one tanh hidden layer, one output
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Genome {
+    pub weights: Vec<f32>,
+}
+
+impl Genome {
+    pub fn random(rng: &mut impl Rng) -> Self {
+        Genome {
+            weights: (0..GENOME_SIZE)
+                .map(|_| rng.gen_range(-INITIAL_WEIGHT..INITIAL_WEIGHT))
+                .collect(),
+        }
+    }
+
+    pub fn flaps(&self, inputs: &[f32; INPUTS]) -> bool {
+        let (hidden_weights, output_weights) = self.weights.split_at((INPUTS + 1) * HIDDEN);
+        let output = hidden_weights
+            .chunks(INPUTS + 1)
+            .zip(output_weights)
+            .map(|(neuron, weight)| {
+                let sum = neuron[INPUTS]
+                    + neuron
+                        .iter()
+                        .zip(inputs)
+                        .map(|(weight, input)| weight * input)
+                        .sum::<f32>();
+                sum.tanh() * weight
+            })
+            .sum::<f32>()
+            + output_weights[HIDDEN];
+        output > 0.
+    }
+
+    //each weight from either parent
+    fn crossover(&self, other: &Genome, rng: &mut impl Rng) -> Genome {
+        Genome {
+            weights: self
+                .weights
+                .iter()
+                .zip(&other.weights)
+                .map(|(a, b)| if rng.gen() { *a } else { *b })
+                .collect(),
+        }
+    }
+
+    fn mutate(&mut self, rng: &mut impl Rng) {
+        for weight in self.weights.iter_mut() {
+            if rng.gen::<f32>() < MUTATION_RATE {
+                *weight += rng.gen_range(-MUTATION_SIZE..MUTATION_SIZE);
+            }
+        }
+    }
+}
+
+//observation scaled to roughly -1..1 for the network
+pub fn network_inputs(observation: &Observation, config: &FlappConfig) -> [f32; INPUTS] {
+    let mut inputs = [0.; INPUTS];
+    inputs[0] = observation.bird_y / (WIN_Y / 2.);
+    inputs[1] = observation.bird_velocity / config.flap_force;
+    for (i, pipe) in observation.pipes.iter().enumerate() {
+        inputs[2 + i * 2] = pipe.dx / (WIN_X / 2.);
+        inputs[3 + i * 2] = pipe.dy / (WIN_Y / 2.);
+    }
+    inputs
+}
+
+//the best genome so far, as saved to disk
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedGenome {
+    pub generation: u32,
+    pub fitness: f32,
+    pub score: u32,
+    pub genome: Genome,
+}
+
+//a population evolving across generations, every generation flies one course together
+#[derive(Resource)]
+pub struct Training {
+    pub genomes: Vec<Genome>,
+    pub generation: u32,
+    pub best: Option<SavedGenome>,
+    //stop after this many generations
+    pub generations: Option<u32>,
+    rng: ChaCha8Rng,
+}
+
+impl Training {
+    pub fn new(population: usize, generations: Option<u32>, seed: u64) -> Self {
+        let population = population.max(MIN_POPULATION);
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        Training {
+            genomes: (0..population).map(|_| Genome::random(&mut rng)).collect(),
+            generation: 1,
+            best: None,
+            generations,
+            rng,
+        }
+    }
+
+    //where the best genome is kept unless told otherwise
+    pub fn path() -> Option<PathBuf> {
+        data_path(BEST_GENOME_FILE)
+    }
+
+    //next generation from the fitness of each genome of this one, the fittest survive as they are
+    pub fn evolve(&mut self, fitness: &[f32]) {
+        let mut ranked: Vec<usize> = (0..self.genomes.len()).collect();
+        ranked.sort_by(|a, b| fitness[*b].total_cmp(&fitness[*a]));
+        let elite = ((self.genomes.len() as f32 * ELITE_SHARE).ceil() as usize).max(1);
+
+        let mut next: Vec<Genome> = ranked[..elite]
+            .iter()
+            .map(|&i| self.genomes[i].clone())
+            .collect();
+        while next.len() < self.genomes.len() {
+            let a = self.tournament(fitness);
+            let b = self.tournament(fitness);
+            let mut child = self.genomes[a].crossover(&self.genomes[b], &mut self.rng);
+            child.mutate(&mut self.rng);
+            next.push(child);
+        }
+        self.genomes = next;
+        self.generation += 1;
+    }
+
+    //fittest of a few genomes picked at random
+    fn tournament(&mut self, fitness: &[f32]) -> usize {
+        let indices: Vec<usize> = (0..self.genomes.len()).collect();
+        *indices
+            .choose_multiple(&mut self.rng, TOURNAMENT_SIZE)
+            .max_by(|a, b| fitness[**a].total_cmp(&fitness[**b]))
+            .unwrap()
+    }
+}
+
+//file the best genome is written to whenever a generation beats it
+#[derive(Resource)]
+pub struct GenomeOutput(pub PathBuf);
+
+//a bird of the population, flown by its genome
+#[derive(Component)]
+pub struct Agent {
+    pub genome: usize,
+    pub alive: bool,
+    pub fitness: f32,
+    flap: bool,
+}
+
+#[derive(Component)]
+pub struct TrainingText;
+
+//flies the `Training` population instead of the player's bird, which has to be inserted before this plugin
+pub struct TrainingPlugin;
+
+impl Plugin for TrainingPlugin {
+    fn build(&self, app: &mut App) {
+        //headless games already go straight into each run
+        if !app.is_plugin_added::<HeadlessPlugin>() {
+            app.add_systems(OnEnter(GameState::Title), start_run)
+                .add_systems(
+                    Update,
+                    skip_countdown.run_if(in_state(GameState::Countdown)),
+                );
+        }
+        app.add_systems(Startup, spawn_training_text)
+            .add_systems(OnEnter(GameState::Countdown), spawn_agents)
+            .add_systems(Update, update_training_text)
+            .add_systems(
+                FixedUpdate,
+                (
+                    think.in_set(PhysicsSet::Input),
+                    (update_agents, end_generation)
+                        .chain()
+                        .in_set(PhysicsSet::Bird),
+                ),
+            );
+    }
+}
+
+fn spawn_agents(
+    mut commands: Commands,
+    config: Res<FlappConfig>,
+    training: Res<Training>,
+    agent_query: Query<Entity, With<Agent>>,
+) {
+    for entity in agent_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for genome in 0..training.genomes.len() {
+        commands.spawn((
+            Transform::IDENTITY.with_scale(Vec3::splat(config.pixel_ratio)),
+            Visibility::default(),
+            physics_transform(Vec2::ZERO),
+            Bird { velocity: 0. },
+            Agent {
+                genome,
+                alive: true,
+                fitness: 0.,
+                flap: false,
+            },
+        ));
+    }
+}
+
+fn think(
+    training: Res<Training>,
+    config: Res<FlappConfig>,
+    mut agent_query: Query<(&mut Agent, &Bird, &PhysicsTransform)>,
+    pair_query: Query<(&PipePair, &PhysicsTransform)>,
+) {
+    for (mut agent, bird, transform) in agent_query.iter_mut() {
+        if !agent.alive {
+            continue;
+        }
+        let observation = observe(bird, transform, pair_query.iter());
+        agent.flap = training.genomes[agent.genome].flaps(&network_inputs(&observation, &config));
+    }
+}
+
+//same flight and collisions as `update_bird`, for every agent still in the air
+fn update_agents(
+    mut agent_query: Query<
+        (
+            &mut Agent,
+            &mut Bird,
+            &mut PhysicsTransform,
+            &PreviousPhysicsTransform,
+            &mut Visibility,
+        ),
+        Without<PipePair>,
+    >,
+    mut pair_query: Query<(
+        Entity,
+        &mut PipePair,
+        &PhysicsTransform,
+        &PreviousPhysicsTransform,
+    )>,
+    time: Res<Time>,
+    game_manager: Res<GameManager>,
+    config: Res<FlappConfig>,
+    masks: Res<CollisionMasks>,
+    mut score: ResMut<Score>,
+) {
+    let window_height = game_manager.window_dimensions.y;
+    for (mut agent, mut bird, mut transform, previous, mut visibility) in agent_query.iter_mut() {
+        if !agent.alive {
+            continue;
+        }
+        let flap = agent.flap;
+        fly(
+            &mut bird.velocity,
+            &mut transform,
+            flap,
+            &config,
+            time.delta_secs(),
+        );
+        let death = tick_death(
+            &mut bird.velocity,
+            &previous.0,
+            &mut transform,
+            pair_query.iter(),
+            &config,
+            &masks,
+            window_height,
+        );
+
+        agent.fitness += 1.;
+        if death.is_some() {
+            agent.alive = false;
+            *visibility = Visibility::Hidden;
+            let miss = pair_query
+                .iter()
+                .filter(|(_, pair, ..)| !pair.passed)
+                .min_by(|(_, _, a, _), (_, _, b, _)| a.translation.x.total_cmp(&b.translation.x))
+                .map_or(0., |(_, pair, pair_transform, _)| {
+                    (pair_transform.translation.y - transform.translation.y).abs() / pair.gap_size
+                });
+            agent.fitness += score.value as f32 * PIPE_FITNESS - miss * MISS_PENALTY;
+        }
+    }
+    //every agent flies at the same x, so the survivors pass each pair together
+    let survivor_x = agent_query
+        .iter()
+        .find(|(agent, ..)| agent.alive)
+        .map(|(_, _, transform, ..)| transform.translation.x);
+    if let Some(survivor_x) = survivor_x {
+        for (_, mut pair, pair_transform, _) in pair_query.iter_mut() {
+            if passes_pair(survivor_x, &pair, pair_transform.translation.x, &config) {
+                pair.passed = true;
+                score.value += 1;
+            }
+        }
+    }
+}
+
+//breeds the next generation once every agent has crashed or the survivors reach `GENERATION_SCORE`
+#[allow(clippy::too_many_arguments)]
+fn end_generation(
+    mut agent_query: Query<&mut Agent>,
+    score: Res<Score>,
+    args: Option<Res<Args>>,
+    output: Option<Res<GenomeOutput>>,
+    mut training: ResMut<Training>,
+    mut run_over: ResMut<RunOver>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if agent_query.iter().any(|agent| agent.alive) && score.value < GENERATION_SCORE {
+        return;
+    }
+    //made it all the way, scored like a bird that died dead centre in the next gap
+    for mut agent in agent_query.iter_mut() {
+        if agent.alive {
+            agent.fitness += score.value as f32 * PIPE_FITNESS;
+        }
+    }
+    let mut fitness = vec![0.; training.genomes.len()];
+    for agent in agent_query.iter() {
+        fitness[agent.genome] = agent.fitness;
+    }
+    let (fittest, best_fitness) = fitness
+        .iter()
+        .copied()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .unwrap_or_default();
+    if training
+        .best
+        .as_ref()
+        .is_none_or(|best| best_fitness > best.fitness)
+    {
+        let best = SavedGenome {
+            generation: training.generation,
+            fitness: best_fitness,
+            score: score.value,
+            genome: training.genomes[fittest].clone(),
+        };
+        if let Some(output) = output {
+            if let Err(err) = save_ron(&output.0, &best) {
+                error!(
+                    "could not save the best genome to {}: {err}",
+                    output.0.display()
+                );
+            }
+        }
+        training.best = Some(best);
+    }
+    if args.is_some_and(|args| args.headless) {
+        println!(
+            "generation {} score {} best fitness {best_fitness:.0}",
+            training.generation, score.value
+        );
+    }
+
+    if training
+        .generations
+        .is_some_and(|generations| training.generation >= generations)
+    {
+        //nothing else ends this run, the ticks left in the frame mustn't end it again
+        run_over.0 = true;
+        exit.send(AppExit::Success);
+        return;
+    }
+    training.evolve(&fitness);
+    next_state.set(GameState::Countdown);
+}
+
+fn spawn_training_text(mut commands: Commands, config: Res<FlappConfig>) {
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: TRAINING_TEXT_SIZE * config.pixel_ratio,
+            ..Default::default()
+        },
+        TextColor(TRAINING_TEXT_COLOR),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(TRAINING_TEXT_PAD * config.pixel_ratio),
+            left: Val::Px(TRAINING_TEXT_PAD * config.pixel_ratio),
+            ..Default::default()
+        },
+        TrainingText,
+    ));
+}
+
+fn update_training_text(
+    training: Res<Training>,
+    agent_query: Query<&Agent>,
+    mut text_query: Query<&mut Text, With<TrainingText>>,
+) {
+    let alive = agent_query.iter().filter(|agent| agent.alive).count();
+    let best = training.best.as_ref().map_or(0., |best| best.fitness);
+    let line = format!(
+        "generation {}\nbest fitness {best:.0}\nalive {alive}/{}",
+        training.generation,
+        training.genomes.len()
+    );
+    for mut text in text_query.iter_mut() {
+        if text.0 != line {
+            text.0 = line.clone();
+        }
+    }
+}
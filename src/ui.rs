@@ -0,0 +1,439 @@
+use bevy::prelude::*;
+
+use crate::{
+    actions::{Action, Actions, Bindings, InputDevices},
+    config::{srgb, FlappConfig},
+    leaderboard::{format_date, submit_score, Leaderboard},
+    rng::{seed_text, GameRng},
+    score::{score_text, Score},
+    settings::Settings,
+    GameState,
+};
+
+//pause screen
+pub const PAUSE_TEXT_SIZE: f32 = 28.;
+pub const PAUSE_TEXT_1: &str = "Flap Flap Away~";
+//hints end up as "press [<first binding>] to start."
+pub const PAUSE_TEXT_2: &str = "to start.";
+pub const PAUSE_TEXT_3: &str = "to resume.";
+pub const GAME_OVER_TEXT: &str = "Game Over~";
+pub const PAUSED_TEXT: &str = "Paused";
+pub const PAUSE_SCREEN_ROW_GAP: f32 = 12.;
+pub const NEW_BEST_TEXT: &str = "new best!";
+
+//leaderboard screen
+pub const LEADERBOARD_KEY: KeyCode = KeyCode::KeyL;
+pub const LEADERBOARD_TITLE: &str = "Leaderboard";
+pub const LEADERBOARD_HINT: &str = "press [l] for the leaderboard.";
+pub const BACK_TEXT: &str = "to go back.";
+pub const LEADERBOARD_EMPTY: &str = "no runs yet.";
+
+//options screen
+pub const OPTIONS_KEY: KeyCode = KeyCode::KeyO;
+pub const OPTIONS_TITLE: &str = "Options";
+pub const OPTIONS_HINT: &str = "press [o] for options.";
+pub const OPTIONS_HELP: &str = "[up/down] select  [enter] rebind  [delete] reset to defaults";
+pub const REBIND_PROMPT: &str = "press any key, button or tap... [esc] to cancel";
+pub const OPTIONS_UP_KEY: KeyCode = KeyCode::ArrowUp;
+pub const OPTIONS_DOWN_KEY: KeyCode = KeyCode::ArrowDown;
+pub const REBIND_KEY: KeyCode = KeyCode::Enter;
+pub const RESET_BINDING_KEY: KeyCode = KeyCode::Delete;
+pub const CANCEL_REBIND_KEY: KeyCode = KeyCode::Escape;
+
+//countdown
+pub const COUNTDOWN_SECONDS: f32 = 3.;
+pub const COUNTDOWN_TEXT_SIZE: f32 = 28.;
+
+#[derive(Component)]
+pub struct PauseScreen;
+
+#[derive(Component)]
+pub struct FinalScoreText;
+
+//row of the options screen under the cursor, and whether it waits for a new binding
+#[derive(Resource, Default)]
+pub struct OptionsMenu {
+    pub selected: usize,
+    pub rebinding: bool,
+}
+
+//one line of the options screen, per `Action::ALL` index
+#[derive(Component)]
+pub struct OptionsRow(pub usize);
+
+#[derive(Resource)]
+pub struct CountdownTimer(pub Timer);
+
+#[derive(Component)]
+pub struct CountdownText;
+
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<OptionsMenu>()
+            .add_systems(OnEnter(GameState::Countdown), start_countdown)
+            .add_systems(OnEnter(GameState::Paused), pause_game)
+            .add_systems(OnExit(GameState::Paused), resume_game)
+            .add_systems(OnEnter(GameState::Title), show_title_screen)
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                show_game_over_screen.after(submit_score),
+            )
+            .add_systems(OnEnter(GameState::Leaderboard), show_leaderboard_screen)
+            .add_systems(OnEnter(GameState::Options), show_options_screen)
+            .add_systems(
+                Update,
+                (
+                    start_game.run_if(
+                        in_state(GameState::Title)
+                            .or(in_state(GameState::GameOver))
+                            .or(in_state(GameState::Leaderboard)),
+                    ),
+                    toggle_leaderboard
+                        .run_if(in_state(GameState::Title).or(in_state(GameState::Leaderboard))),
+                    //leaving is checked first so escape only cancels a rebind
+                    toggle_options
+                        .before(navigate_options)
+                        .run_if(in_state(GameState::Title).or(in_state(GameState::Options))),
+                    (navigate_options, update_options_screen)
+                        .chain()
+                        .run_if(in_state(GameState::Options)),
+                    update_final_score_text
+                        .run_if(in_state(GameState::GameOver).and(resource_changed::<Score>)),
+                    update_countdown.run_if(in_state(GameState::Countdown)),
+                    toggle_pause
+                        .run_if(in_state(GameState::Playing).or(in_state(GameState::Paused))),
+                    restart_run.run_if(
+                        in_state(GameState::Playing)
+                            .or(in_state(GameState::Paused))
+                            .or(in_state(GameState::GameOver)),
+                    ),
+                ),
+            );
+    }
+}
+
+fn start_game(actions: Res<Actions>, mut next_state: ResMut<NextState<GameState>>) {
+    if actions.just_pressed(Action::Confirm) {
+        next_state.set(GameState::Countdown);
+    }
+}
+
+fn restart_run(actions: Res<Actions>, mut next_state: ResMut<NextState<GameState>>) {
+    if actions.just_pressed(Action::Restart) {
+        next_state.set(GameState::Countdown);
+    }
+}
+
+fn toggle_leaderboard(
+    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<Actions>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    match state.get() {
+        GameState::Leaderboard
+            if keys.just_pressed(LEADERBOARD_KEY) || actions.just_pressed(Action::Back) =>
+        {
+            next_state.set(GameState::Title)
+        }
+        GameState::Title if keys.just_pressed(LEADERBOARD_KEY) => {
+            next_state.set(GameState::Leaderboard)
+        }
+        _ => {}
+    }
+}
+
+fn start_countdown(mut commands: Commands, config: Res<FlappConfig>) {
+    commands.insert_resource(CountdownTimer(Timer::from_seconds(
+        COUNTDOWN_SECONDS,
+        TimerMode::Once,
+    )));
+    commands.spawn((
+        Text2d::new(countdown_text(COUNTDOWN_SECONDS)),
+        TextFont {
+            font_size: COUNTDOWN_TEXT_SIZE,
+            ..Default::default()
+        },
+        TextColor(srgb(config.pause_text_color)),
+        Transform::from_xyz(0., 0., 1.).with_scale(Vec3::splat(config.pixel_ratio)),
+        CountdownText,
+        StateScoped(GameState::Countdown),
+    ));
+}
+
+fn countdown_text(remaining: f32) -> String {
+    format!("{}", remaining.ceil().max(1.) as u32)
+}
+
+fn update_countdown(
+    time: Res<Time>,
+    mut timer: ResMut<CountdownTimer>,
+    mut text_query: Query<&mut Text2d, With<CountdownText>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    timer.0.tick(time.delta());
+    if let Ok(mut text) = text_query.get_single_mut() {
+        text.0 = countdown_text(timer.0.remaining_secs());
+    }
+    if timer.0.finished() {
+        next_state.set(GameState::Playing);
+    }
+}
+
+fn toggle_pause(
+    actions: Res<Actions>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if actions.just_pressed(Action::Pause) {
+        next_state.set(match state.get() {
+            GameState::Paused => GameState::Playing,
+            _ => GameState::Paused,
+        });
+    }
+}
+
+fn pause_game(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    config: Res<FlappConfig>,
+    bindings: Res<Bindings>,
+) {
+    time.pause();
+    spawn_pause_screen(&mut commands, &config, GameState::Paused).with_children(|screen| {
+        screen.spawn(pause_text(&config, PAUSED_TEXT, PAUSE_TEXT_SIZE));
+        screen.spawn(pause_text(
+            &config,
+            hint(&bindings, Action::Pause, PAUSE_TEXT_3),
+            PAUSE_TEXT_SIZE / 3.,
+        ));
+    });
+}
+
+fn resume_game(mut time: ResMut<Time<Virtual>>) {
+    time.unpause();
+}
+
+//full screen overlay which is torn down when leaving `state`
+fn spawn_pause_screen<'a>(
+    commands: &'a mut Commands,
+    config: &FlappConfig,
+    state: GameState,
+) -> EntityCommands<'a> {
+    commands.spawn((
+        Node {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            row_gap: Val::Px(PAUSE_SCREEN_ROW_GAP * config.pixel_ratio),
+            ..Default::default()
+        },
+        PauseScreen,
+        StateScoped(state),
+    ))
+}
+
+fn pause_text(config: &FlappConfig, text: impl Into<String>, size: f32) -> impl Bundle {
+    (
+        Text::new(text),
+        TextFont {
+            font_size: size * config.pixel_ratio,
+            ..Default::default()
+        },
+        TextColor(srgb(config.pause_text_color)),
+    )
+}
+
+//"press [<first binding>] <what>"
+fn hint(bindings: &Bindings, action: Action, what: &str) -> String {
+    format!("press [{}] {what}", bindings.label(action))
+}
+
+fn show_title_screen(mut commands: Commands, config: Res<FlappConfig>, bindings: Res<Bindings>) {
+    spawn_pause_screen(&mut commands, &config, GameState::Title).with_children(|screen| {
+        screen.spawn(pause_text(&config, PAUSE_TEXT_1, PAUSE_TEXT_SIZE));
+        screen.spawn(pause_text(
+            &config,
+            hint(&bindings, Action::Confirm, PAUSE_TEXT_2),
+            PAUSE_TEXT_SIZE / 3.,
+        ));
+        screen.spawn(pause_text(&config, LEADERBOARD_HINT, PAUSE_TEXT_SIZE / 4.));
+        screen.spawn(pause_text(&config, OPTIONS_HINT, PAUSE_TEXT_SIZE / 4.));
+    });
+}
+
+fn show_leaderboard_screen(
+    mut commands: Commands,
+    config: Res<FlappConfig>,
+    bindings: Res<Bindings>,
+    leaderboard: Res<Leaderboard>,
+) {
+    spawn_pause_screen(&mut commands, &config, GameState::Leaderboard).with_children(|screen| {
+        screen.spawn(pause_text(&config, LEADERBOARD_TITLE, PAUSE_TEXT_SIZE));
+        if leaderboard.entries.is_empty() {
+            screen.spawn(pause_text(&config, LEADERBOARD_EMPTY, PAUSE_TEXT_SIZE / 3.));
+        }
+        for (rank, entry) in leaderboard.entries.iter().enumerate() {
+            screen.spawn(pause_text(
+                &config,
+                format!(
+                    "{}. {}  {}  {}  seed {}",
+                    rank + 1,
+                    entry.score,
+                    entry.name,
+                    format_date(entry.date),
+                    entry.seed
+                ),
+                PAUSE_TEXT_SIZE / 3.,
+            ));
+        }
+        screen.spawn(pause_text(
+            &config,
+            hint(&bindings, Action::Back, BACK_TEXT),
+            PAUSE_TEXT_SIZE / 4.,
+        ));
+    });
+}
+
+fn show_game_over_screen(
+    mut commands: Commands,
+    config: Res<FlappConfig>,
+    bindings: Res<Bindings>,
+    score: Res<Score>,
+    rng: Res<GameRng>,
+    leaderboard: Res<Leaderboard>,
+) {
+    spawn_pause_screen(&mut commands, &config, GameState::GameOver).with_children(|screen| {
+        screen.spawn(pause_text(&config, GAME_OVER_TEXT, PAUSE_TEXT_SIZE));
+        screen.spawn((
+            pause_text(&config, score_text(score.value), PAUSE_TEXT_SIZE / 1.5),
+            FinalScoreText,
+        ));
+        if leaderboard.is_new_best() {
+            screen.spawn(pause_text(&config, NEW_BEST_TEXT, PAUSE_TEXT_SIZE / 2.));
+        }
+        screen.spawn(pause_text(
+            &config,
+            hint(&bindings, Action::Confirm, PAUSE_TEXT_2),
+            PAUSE_TEXT_SIZE / 3.,
+        ));
+        screen.spawn(pause_text(
+            &config,
+            seed_text(rng.seed()),
+            PAUSE_TEXT_SIZE / 4.,
+        ));
+    });
+}
+
+fn update_final_score_text(score: Res<Score>, mut query: Query<&mut Text, With<FinalScoreText>>) {
+    for mut text in query.iter_mut() {
+        text.0 = score_text(score.value);
+    }
+}
+
+fn toggle_options(
+    keys: Res<ButtonInput<KeyCode>>,
+    actions: Res<Actions>,
+    state: Res<State<GameState>>,
+    mut menu: ResMut<OptionsMenu>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    match state.get() {
+        GameState::Options
+            if !menu.rebinding
+                && (keys.just_pressed(OPTIONS_KEY) || actions.just_pressed(Action::Back)) =>
+        {
+            next_state.set(GameState::Title)
+        }
+        GameState::Title if keys.just_pressed(OPTIONS_KEY) => {
+            *menu = OptionsMenu::default();
+            next_state.set(GameState::Options)
+        }
+        _ => {}
+    }
+}
+
+fn show_options_screen(mut commands: Commands, config: Res<FlappConfig>, bindings: Res<Bindings>) {
+    spawn_pause_screen(&mut commands, &config, GameState::Options).with_children(|screen| {
+        screen.spawn(pause_text(&config, OPTIONS_TITLE, PAUSE_TEXT_SIZE));
+        for index in 0..Action::ALL.len() {
+            screen.spawn((
+                pause_text(&config, "", PAUSE_TEXT_SIZE / 3.),
+                OptionsRow(index),
+            ));
+        }
+        screen.spawn(pause_text(&config, OPTIONS_HELP, PAUSE_TEXT_SIZE / 4.));
+        screen.spawn(pause_text(
+            &config,
+            hint(&bindings, Action::Back, BACK_TEXT),
+            PAUSE_TEXT_SIZE / 4.,
+        ));
+    });
+}
+
+//moves the cursor, or hands the next input to the selected action while rebinding
+fn navigate_options(
+    keys: Res<ButtonInput<KeyCode>>,
+    devices: InputDevices,
+    mut menu: ResMut<OptionsMenu>,
+    mut bindings: ResMut<Bindings>,
+) {
+    let action = Action::ALL[menu.selected];
+    if menu.rebinding {
+        if keys.just_pressed(CANCEL_REBIND_KEY) {
+            menu.rebinding = false;
+        } else if let Some(binding) = devices.any_just_pressed() {
+            bindings.rebind(action, binding);
+            menu.rebinding = false;
+            save_bindings(&bindings);
+        }
+        return;
+    }
+    let rows = Action::ALL.len();
+    if keys.just_pressed(OPTIONS_UP_KEY) {
+        menu.selected = (menu.selected + rows - 1) % rows;
+    }
+    if keys.just_pressed(OPTIONS_DOWN_KEY) {
+        menu.selected = (menu.selected + 1) % rows;
+    }
+    if keys.just_pressed(REBIND_KEY) {
+        menu.rebinding = true;
+    }
+    if keys.just_pressed(RESET_BINDING_KEY) {
+        bindings.reset(action);
+        save_bindings(&bindings);
+    }
+}
+
+fn save_bindings(bindings: &Bindings) {
+    let settings = Settings {
+        bindings: bindings.clone(),
+    };
+    if let Err(err) = settings.save() {
+        error!("couldn't save the settings: {err}");
+    }
+}
+
+fn update_options_screen(
+    menu: Res<OptionsMenu>,
+    bindings: Res<Bindings>,
+    mut row_query: Query<(&mut Text, &OptionsRow)>,
+) {
+    for (mut text, row) in row_query.iter_mut() {
+        let action = Action::ALL[row.0];
+        let cursor = if row.0 == menu.selected { "> " } else { "  " };
+        let bound = if row.0 == menu.selected && menu.rebinding {
+            String::from(REBIND_PROMPT)
+        } else {
+            bindings.list(action)
+        };
+        let line = format!("{cursor}{}: {bound}", action.name());
+        if text.0 != line {
+            text.0 = line;
+        }
+    }
+}
@@ -0,0 +1,198 @@
+use bevy::{
+    image::ImageSampler,
+    prelude::*,
+    render::{
+        camera::{RenderTarget, ScalingMode},
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages},
+        view::RenderLayers,
+    },
+    window::{PrimaryWindow, WindowMode},
+};
+
+use crate::{
+    config::{srgb, FlappConfig},
+    score::spawn_score_text,
+    WIN_X, WIN_Y,
+};
+
+//view
+pub const FULLSCREEN_KEY: KeyCode = KeyCode::F11;
+//drawn around the play field when letterboxing is on
+pub const LETTERBOX_COLOR: Color = Color::BLACK;
+//the upscaled play field lives on its own layer so the world camera doesn't draw it again
+pub const SCREEN_LAYER: usize = 1;
+
+//the play field at one pixel per art pixel
+#[derive(Resource)]
+pub struct LowResTarget(pub Handle<Image>);
+
+#[derive(Component)]
+pub struct WorldCamera;
+
+//shows `LowResTarget` in the window
+#[derive(Component)]
+pub struct Screen;
+
+//the world is drawn at the art's own resolution into `LowResTarget`,
+//which is scaled up to the window by the largest whole factor that fits
+pub struct ViewPlugin;
+
+impl Plugin for ViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_view).add_systems(
+            Update,
+            (
+                toggle_fullscreen,
+                apply_config_to_view.run_if(resource_changed::<FlappConfig>),
+                fit_screen,
+            )
+                .chain(),
+        );
+    }
+}
+
+//pixels of the low resolution play field
+pub fn low_res_size(config: &FlappConfig) -> UVec2 {
+    (Vec2::new(WIN_X, WIN_Y) / config.pixel_ratio)
+        .ceil()
+        .as_uvec2()
+}
+
+fn low_res_image(size: UVec2) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0; 4],
+        TextureFormat::Bgra8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    image.sampler = ImageSampler::nearest();
+    image
+}
+
+fn setup_view(mut commands: Commands, mut images: ResMut<Assets<Image>>, config: Res<FlappConfig>) {
+    let target = images.add(low_res_image(low_res_size(&config)));
+
+    //world camera, one pixel of the target is one art pixel
+    commands.spawn((
+        Camera2d,
+        Camera {
+            order: -1,
+            target: RenderTarget::Image(target.clone()),
+            clear_color: ClearColorConfig::Custom(srgb(config.background_color)),
+            ..Default::default()
+        },
+        OrthographicProjection {
+            scaling_mode: ScalingMode::WindowSize,
+            scale: config.pixel_ratio,
+            ..OrthographicProjection::default_2d()
+        },
+        Msaa::Off,
+        WorldCamera,
+    ));
+
+    //window camera, draws the upscaled play field and the ui on top
+    commands.spawn((
+        Camera2d,
+        Msaa::Off,
+        RenderLayers::layer(SCREEN_LAYER),
+        IsDefaultUiCamera,
+    ));
+    commands.spawn((
+        Sprite::from_image(target.clone()),
+        RenderLayers::layer(SCREEN_LAYER),
+        Screen,
+    ));
+    commands.insert_resource(LowResTarget(target));
+
+    //score
+    spawn_score_text(&mut commands, &config, Vec2::new(WIN_X, WIN_Y));
+}
+
+//background, letterbox and the low resolution follow the config
+fn apply_config_to_view(
+    config: Res<FlappConfig>,
+    target: Res<LowResTarget>,
+    mut images: ResMut<Assets<Image>>,
+    mut clear_color: ResMut<ClearColor>,
+    mut camera_query: Query<(&mut Camera, &mut OrthographicProjection), With<WorldCamera>>,
+) {
+    clear_color.0 = match config.letterbox {
+        true => LETTERBOX_COLOR,
+        false => srgb(config.background_color),
+    };
+    for (mut camera, mut projection) in camera_query.iter_mut() {
+        camera.clear_color = ClearColorConfig::Custom(srgb(config.background_color));
+        projection.scale = config.pixel_ratio;
+    }
+    let size = low_res_size(&config);
+    if let Some(image) = images.get_mut(&target.0) {
+        if image.size() != size {
+            image.resize(Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            });
+        }
+    }
+}
+
+//scale the play field by the largest whole factor that fits and keep its pixels on the window's,
+//ui is scaled along with it
+fn fit_screen(
+    config: Res<FlappConfig>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut screen_query: Query<&mut Transform, With<Screen>>,
+    mut ui_scale: ResMut<UiScale>,
+) {
+    let Ok(window) = window_query.get_single() else {
+        return;
+    };
+    let window_size = window.physical_size().as_vec2();
+    let size = low_res_size(&config).as_vec2();
+    let factor = (window_size / size).min_element().floor().max(1.);
+    let scaled = size * factor;
+    //line the left and bottom edges up with whole window pixels
+    let corner = ((window_size - scaled) / 2.).floor();
+    let offset = corner + scaled / 2. - window_size / 2.;
+
+    let scale_factor = window.scale_factor();
+    for mut transform in screen_query.iter_mut() {
+        let translation = (offset / scale_factor).extend(0.);
+        let scale = Vec3::splat(factor / scale_factor);
+        if transform.translation != translation || transform.scale != scale {
+            transform.translation = translation;
+            transform.scale = scale;
+        }
+    }
+    let ui = factor / config.pixel_ratio / scale_factor;
+    if ui_scale.0 != ui {
+        ui_scale.0 = ui;
+    }
+}
+
+//F11 or alt+enter
+fn toggle_fullscreen(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let alt = keys.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]);
+    let pressed = keys.just_pressed(FULLSCREEN_KEY) || (alt && keys.just_pressed(KeyCode::Enter));
+    if !pressed {
+        return;
+    }
+    let Ok(mut window) = window_query.get_single_mut() else {
+        return;
+    };
+    window.mode = match window.mode {
+        WindowMode::Windowed => WindowMode::BorderlessFullscreen(MonitorSelection::Current),
+        _ => WindowMode::Windowed,
+    };
+}
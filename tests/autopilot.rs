@@ -0,0 +1,36 @@
+mod common;
+
+use common::{headless_app, state};
+use flapp::{cli::Args, config::FlappConfig, physics::PhysicsTick, score::Score, GameState};
+
+//plays a headless run with the autopilot for up to `ticks`, returning the final state and score
+fn fly(config: FlappConfig, seed: u64, ticks: u64) -> (GameState, u32) {
+    let args = Args {
+        seed: Some(seed),
+        autopilot: true,
+        ..Default::default()
+    };
+    let mut app = headless_app(config, args, 1);
+    while app.world().resource::<PhysicsTick>().0 < ticks && state(&app) != GameState::GameOver {
+        app.update();
+    }
+    (state(&app), app.world().resource::<Score>().value)
+}
+
+#[test]
+fn default_skill_gets_through_the_pipes() {
+    for seed in 0..5 {
+        let (state, score) = fly(FlappConfig::default(), seed, 60 * 60);
+        assert_eq!(state, GameState::Playing, "seed {seed}, score {score}");
+        assert!(score >= 20, "seed {seed}, score {score}");
+    }
+}
+
+#[test]
+fn slow_reactions_crash() {
+    let config = FlappConfig {
+        autopilot_reaction: 1.,
+        ..Default::default()
+    };
+    assert_eq!(fly(config, 0, 60 * 60).0, GameState::GameOver);
+}
@@ -0,0 +1,29 @@
+//each test binary uses its own share of these
+#![allow(dead_code)]
+
+use bevy::{prelude::*, state::app::StatesPlugin, time::TimeUpdateStrategy};
+use flapp::{cli::Args, config::FlappConfig, headless::HeadlessPlugin, GameState};
+
+//a headless game playing one run at `ticks_per_frame` physics ticks per update
+pub fn headless_app(config: FlappConfig, args: Args, ticks_per_frame: u32) -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin))
+        .insert_resource(config)
+        .insert_resource(args)
+        .add_plugins(HeadlessPlugin { runs: 1 });
+    let timestep = app.world().resource::<Time<Fixed>>().timestep();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(
+        timestep * ticks_per_frame,
+    ));
+    app
+}
+
+pub fn state(app: &App) -> GameState {
+    *app.world().resource::<State<GameState>>().get()
+}
+
+pub fn run_until_game_over(app: &mut App) {
+    while state(app) != GameState::GameOver {
+        app.update();
+    }
+}
@@ -0,0 +1,67 @@
+use flapp::{
+    config::FlappConfig,
+    env::{EnvAction, FlappEnv, Observation},
+};
+
+//plays a run flapping every `period` ticks, returning every observation and the total reward
+fn play(env: &mut FlappEnv, seed: u64, period: u64) -> (Vec<Observation>, f32) {
+    let mut observations = vec![env.reset(seed)];
+    let mut total = 0.;
+    for tick in 0.. {
+        let action = if tick % period == 0 {
+            EnvAction::Flap
+        } else {
+            EnvAction::Idle
+        };
+        let (observation, reward, done) = env.step(action);
+        observations.push(observation);
+        total += reward;
+        if done {
+            break;
+        }
+    }
+    (observations, total)
+}
+
+#[test]
+fn same_seed_plays_out_the_same() {
+    let mut env = FlappEnv::new(FlappConfig::default()).unwrap();
+    let first = play(&mut env, 7, 6);
+    let second = play(&mut env, 7, 6);
+    assert_eq!(first, second);
+
+    let mut other = FlappEnv::new(FlappConfig::default()).unwrap();
+    assert_eq!(play(&mut other, 7, 6), first);
+}
+
+#[test]
+fn idling_falls_to_the_ground() {
+    let mut env = FlappEnv::new(FlappConfig::default()).unwrap();
+    let start = env.reset(1);
+    assert_eq!(start.bird_y, 0.);
+    assert!(start.pipes.iter().all(|pipe| pipe.dx > 0.));
+
+    let mut ticks = 0;
+    loop {
+        let (observation, reward, done) = env.step(EnvAction::Idle);
+        ticks += 1;
+        if done {
+            assert!(reward < 0.);
+            break;
+        }
+        assert!(observation.bird_velocity < 0.);
+    }
+    assert_eq!(env.tick(), ticks);
+    assert_eq!(env.score(), 0);
+    //a finished run stays finished until the next reset
+    assert!(env.step(EnvAction::Flap).2);
+}
+
+#[test]
+fn invalid_config_is_rejected() {
+    let config = FlappConfig {
+        tick_rate: 0.,
+        ..Default::default()
+    };
+    assert!(FlappEnv::new(config).is_err());
+}
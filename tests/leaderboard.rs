@@ -0,0 +1,58 @@
+use flapp::leaderboard::{format_date, Leaderboard, LeaderboardEntry, LEADERBOARD_SIZE};
+
+fn entry(score: u32, name: &str) -> LeaderboardEntry {
+    LeaderboardEntry {
+        score,
+        date: 0,
+        seed: 0,
+        name: String::from(name),
+    }
+}
+
+fn names(leaderboard: &Leaderboard) -> Vec<&str> {
+    leaderboard
+        .entries
+        .iter()
+        .map(|entry| entry.name.as_str())
+        .collect()
+}
+
+#[test]
+fn runs_are_ranked_by_score_ties_go_behind() {
+    let mut leaderboard = Leaderboard::default();
+    assert_eq!(leaderboard.submit(entry(5, "a")), Some(0));
+    assert!(leaderboard.is_new_best());
+    assert_eq!(leaderboard.submit(entry(9, "b")), Some(0));
+    assert_eq!(leaderboard.submit(entry(5, "c")), Some(2));
+    assert!(!leaderboard.is_new_best());
+    assert_eq!(leaderboard.submit(entry(7, "d")), Some(1));
+    assert_eq!(names(&leaderboard), ["b", "d", "a", "c"]);
+}
+
+#[test]
+fn the_table_keeps_only_the_best() {
+    let mut leaderboard = Leaderboard::default();
+    for score in 1..=LEADERBOARD_SIZE as u32 {
+        leaderboard.submit(entry(score, "full"));
+    }
+    assert_eq!(leaderboard.submit(entry(1, "tied last")), None);
+    assert_eq!(leaderboard.last_rank, None);
+    assert_eq!(
+        leaderboard.submit(entry(2, "last")),
+        Some(LEADERBOARD_SIZE - 1)
+    );
+    assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+    assert_eq!(leaderboard.entries.last().unwrap().name, "last");
+    assert_eq!(leaderboard.submit(entry(100, "best")), Some(0));
+    assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+    assert!(leaderboard.entries.iter().all(|entry| entry.name != "last"));
+}
+
+#[test]
+fn dates_are_formatted_in_utc() {
+    assert_eq!(format_date(0), "1970-01-01");
+    assert_eq!(format_date(946684799), "1999-12-31");
+    assert_eq!(format_date(951782400), "2000-02-29");
+    assert_eq!(format_date(1709164800), "2024-02-29");
+    assert_eq!(format_date(1709251200), "2024-03-01");
+}
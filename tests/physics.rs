@@ -0,0 +1,35 @@
+mod common;
+
+use common::{headless_app, run_until_game_over};
+use flapp::{bird::LastDeath, cli::Args, config::FlappConfig, physics::PhysicsTick, score::Score};
+
+//plays a headless run with a sloppy autopilot at `ticks_per_frame` physics ticks per update,
+//returning the ticks played and the score it died on
+fn fly(seed: u64, ticks_per_frame: u32) -> (u64, u32) {
+    let config = FlappConfig {
+        autopilot_reaction: 0.3,
+        ..Default::default()
+    };
+    let args = Args {
+        seed: Some(seed),
+        autopilot: true,
+        ..Default::default()
+    };
+    let mut app = headless_app(config, args, ticks_per_frame);
+    run_until_game_over(&mut app);
+    assert!(app.world().resource::<LastDeath>().0.is_some());
+    (
+        app.world().resource::<PhysicsTick>().0,
+        app.world().resource::<Score>().value,
+    )
+}
+
+#[test]
+fn frame_rate_doesnt_change_the_run() {
+    for seed in 0..3 {
+        let run = fly(seed, 1);
+        for ticks_per_frame in [3, 7] {
+            assert_eq!(fly(seed, ticks_per_frame), run, "seed {seed}");
+        }
+    }
+}
@@ -0,0 +1,43 @@
+use flapp::{
+    cli::Args,
+    config::FlappConfig,
+    env::FlappEnv,
+    protocol::{serve, Reply},
+};
+
+fn replies(input: &str) -> Vec<Reply> {
+    let mut env = FlappEnv::new(FlappConfig::default()).unwrap();
+    let mut output = Vec::new();
+    serve(&mut env, &Args::default(), input.as_bytes(), &mut output).unwrap();
+    String::from_utf8(output)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn every_line_gets_an_answer() {
+    let replies = replies(
+        "{\"cmd\":\"reset\",\"seed\":3}\n\n{\"cmd\":\"step\",\"flap\":true}\nflap\n{\"cmd\":\"step\"}\n",
+    );
+    assert_eq!(replies.len(), 4);
+    let ticks: Vec<Option<u64>> = replies
+        .iter()
+        .map(|reply| match reply {
+            Reply::State { tick, seed, .. } => {
+                assert_eq!(*seed, 3);
+                Some(*tick)
+            }
+            Reply::Error { .. } => None,
+        })
+        .collect();
+    assert_eq!(ticks, [Some(0), Some(1), None, Some(2)]);
+}
+
+#[test]
+fn same_commands_same_replies() {
+    let input = "{\"cmd\":\"reset\",\"seed\":9}\n".to_string()
+        + &"{\"cmd\":\"step\",\"flap\":true}\n{\"cmd\":\"step\"}\n".repeat(50);
+    assert_eq!(replies(&input), replies(&input));
+}
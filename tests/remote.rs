@@ -0,0 +1,116 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::TcpStream,
+    sync::mpsc::{channel, Receiver},
+    thread,
+    time::{Duration, Instant},
+};
+
+use bevy::{prelude::*, state::app::StatesPlugin};
+use flapp::{
+    bird::FlapInput,
+    config::FlappConfig,
+    headless::HeadlessPlugin,
+    physics::PhysicsTick,
+    remote::{RemoteAddress, RemotePlugin, RemoteServer, TickState},
+    GameState,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+//a headless game on a system picked port, with a client connected to it
+fn connect() -> (App, TcpStream, Receiver<String>) {
+    let server = RemoteServer::bind(&"0".parse().unwrap()).unwrap();
+    let RemoteAddress::Tcp(address) = *server.address() else {
+        unreachable!()
+    };
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin))
+        .insert_resource(FlappConfig::default())
+        .insert_resource(server)
+        .add_plugins((HeadlessPlugin { runs: 1 }, RemotePlugin));
+
+    let client = TcpStream::connect(address).unwrap();
+    let reader = BufReader::new(client.try_clone().unwrap());
+    let (sender, lines) = channel();
+    thread::spawn(move || {
+        for line in reader.lines() {
+            if sender.send(line.unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+    (app, client, lines)
+}
+
+//keeps the game running until the client gets a line
+fn next_line(app: &mut App, lines: &Receiver<String>) -> String {
+    let start = Instant::now();
+    loop {
+        if let Ok(line) = lines.try_recv() {
+            return line;
+        }
+        assert!(start.elapsed() < TIMEOUT, "no reply from the game");
+        app.update();
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn subscribers_get_every_tick() {
+    let (mut app, mut client, lines) = connect();
+    client.write_all(b"{\"cmd\":\"subscribe\"}\n").unwrap();
+
+    let mut last_tick = 0;
+    loop {
+        let state: TickState = serde_json::from_str(&next_line(&mut app, &lines)).unwrap();
+        if state.state == GameState::GameOver {
+            assert_eq!(state.tick, last_tick);
+            break;
+        }
+        assert_eq!(state.tick, last_tick + 1);
+        assert!(state.bird.unwrap().velocity <= 0.);
+        last_tick = state.tick;
+    }
+}
+
+#[test]
+fn flaps_are_injected() {
+    let (mut app, mut client, lines) = connect();
+    client.write_all(b"{\"cmd\":\"subscribe\"}\n").unwrap();
+    let state: TickState = serde_json::from_str(&next_line(&mut app, &lines)).unwrap();
+    assert_eq!(state.state, GameState::Playing);
+
+    //the game is held still until the flap has made it through, however long that takes
+    client.write_all(b"{\"cmd\":\"flap\"}\n").unwrap();
+    let start = Instant::now();
+    while !app.world().resource::<FlapInput>().pending {
+        assert!(start.elapsed() < TIMEOUT, "the flap never arrived");
+        app.world_mut().run_schedule(Update);
+        thread::sleep(Duration::from_millis(1));
+    }
+    let flap_tick = app.world().resource::<PhysicsTick>().0;
+    loop {
+        let state: TickState = serde_json::from_str(&next_line(&mut app, &lines)).unwrap();
+        if state.tick > flap_tick {
+            assert!(state.bird.unwrap().velocity > 0.);
+            break;
+        }
+    }
+
+    client.write_all(b"flap\n").unwrap();
+    while !next_line(&mut app, &lines).contains("error") {}
+}
+
+#[cfg(unix)]
+#[test]
+fn stale_sockets_are_replaced() {
+    let path = std::env::temp_dir().join(format!("flapp-remote-{}.sock", std::process::id()));
+    //a listener dropped without cleaning up, like a game that crashed
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+    let server = RemoteServer::bind(&RemoteAddress::Unix(path.clone())).unwrap();
+    std::os::unix::net::UnixStream::connect(&path).unwrap();
+    drop(server);
+    assert!(!path.exists());
+}
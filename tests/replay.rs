@@ -0,0 +1,77 @@
+mod common;
+
+use std::{env, fs, process};
+
+use bevy::prelude::*;
+use common::{headless_app, run_until_game_over};
+use flapp::{
+    cli::Args,
+    config::FlappConfig,
+    physics::PhysicsTick,
+    replay::{config_hash, Replay, ReplayOutput, ReplayPlayback},
+    score::Score,
+};
+
+//plays one headless run at `ticks_per_frame` physics ticks per update, returning its score and ticks
+fn play(args: Args, resource: impl Resource, ticks_per_frame: u32) -> (u32, u64) {
+    let config = FlappConfig {
+        autopilot_reaction: 0.3,
+        ..Default::default()
+    };
+    let mut app = headless_app(config, args, ticks_per_frame);
+    app.insert_resource(resource);
+    run_until_game_over(&mut app);
+    (
+        app.world().resource::<Score>().value,
+        app.world().resource::<PhysicsTick>().0,
+    )
+}
+
+#[test]
+fn recorded_runs_play_back_the_same() {
+    let path = env::temp_dir().join(format!("flapp-replay-{}.ron", process::id()));
+    let recorded = play(
+        Args {
+            seed: Some(3),
+            autopilot: true,
+            ..Default::default()
+        },
+        ReplayOutput(path.clone()),
+        1,
+    );
+    let replay = Replay::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(recorded.0 > 0);
+    assert_eq!((replay.score, replay.ticks), recorded);
+
+    //played back at another frame rate, without the autopilot
+    let seed = replay.seed;
+    let played = play(
+        Args {
+            seed: Some(seed),
+            ..Default::default()
+        },
+        ReplayPlayback::new(replay),
+        4,
+    );
+    assert_eq!(played, recorded);
+}
+
+#[test]
+fn looks_dont_change_the_config_hash() {
+    let config = FlappConfig::default();
+    let restyled = FlappConfig {
+        background_color: [0., 0., 0.],
+        score_text_color: [1., 1., 1.],
+        letterbox: false,
+        autopilot_aim_noise: 0.,
+        player_name: Some(String::from("someone")),
+        ..Default::default()
+    };
+    assert_eq!(config_hash(&config), config_hash(&restyled));
+    let heavier = FlappConfig {
+        gravity: config.gravity * 2.,
+        ..Default::default()
+    };
+    assert_ne!(config_hash(&config), config_hash(&heavier));
+}
@@ -0,0 +1,64 @@
+mod common;
+
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use common::{headless_app, state};
+use flapp::{
+    cli::Args,
+    config::FlappConfig,
+    obstacles::{PipePair, OBSTACLE_AMOUNT, OBSTACLE_WIDTH},
+    physics::{PhysicsTick, PhysicsTransform},
+    score::Score,
+    GameState,
+};
+
+const TICKS: u64 = 60 * 60;
+
+fn game(ticks_per_frame: u32) -> App {
+    let config = FlappConfig {
+        autopilot_aim_noise: 0.,
+        ..Default::default()
+    };
+    let args = Args {
+        seed: Some(1),
+        autopilot: true,
+        ..Default::default()
+    };
+    headless_app(config, args, ticks_per_frame)
+}
+
+fn tick(app: &App) -> u64 {
+    app.world().resource::<PhysicsTick>().0
+}
+
+#[test]
+fn each_pair_scores_once() {
+    let mut app = game(1);
+    let reach = OBSTACLE_WIDTH * FlappConfig::default().pixel_ratio / 2.;
+    //trailing edge of every pair on the last tick, a pair is flown through when it crosses the bird
+    let mut edges: HashMap<Entity, f32> = HashMap::new();
+    let mut flown_through = 0;
+    while tick(&app) < TICKS {
+        app.update();
+        let world = app.world_mut();
+        let mut pair_query = world.query::<(Entity, &PipePair, &PhysicsTransform)>();
+        for (entity, _, transform) in pair_query.iter(world) {
+            let edge = transform.translation.x + reach;
+            if edges.insert(entity, edge).is_some_and(|last| last >= 0.) && edge < 0. {
+                flown_through += 1;
+            }
+        }
+    }
+    assert_eq!(state(&app), GameState::Playing);
+    //some pairs came round again after being recycled
+    assert!(flown_through > OBSTACLE_AMOUNT as u32);
+    assert_eq!(app.world().resource::<Score>().value, flown_through);
+
+    let mut fast = game(4);
+    while tick(&fast) < TICKS {
+        fast.update();
+    }
+    assert_eq!(tick(&fast), TICKS);
+    assert_eq!(fast.world().resource::<Score>().value, flown_through);
+}
@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, state::app::StatesPlugin, time::TimeUpdateStrategy};
+use flapp::{
+    collision::Hitbox, config::FlappConfig, obstacles::PipePair, physics::PhysicsTransform,
+    FlappCorePlugin, GameState,
+};
+
+//a single physics tick long enough to carry the first pipe from ahead of the bird to behind it
+const GIANT_DELTA: Duration = Duration::from_secs(2);
+
+const HITBOXES: [Hitbox; 3] = [Hitbox::Circle, Hitbox::Capsule, Hitbox::PixelMask];
+
+//runs one tick of `GIANT_DELTA` with the first pair placed just ahead of the bird and its gap at `gap_y`
+fn run_giant_tick(hitbox: Hitbox, gap_y: f32) -> GameState {
+    let config = FlappConfig {
+        hitbox,
+        //keep the bird level, so only the pipe can end the run
+        gravity: 0.,
+        obstacle_vertical_offset: 0.,
+        tick_rate: 1. / GIANT_DELTA.as_secs_f32(),
+        ..Default::default()
+    };
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin))
+        .insert_resource(config)
+        .add_plugins(FlappCorePlugin)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(GIANT_DELTA));
+    app.world_mut()
+        .resource_mut::<Time<Virtual>>()
+        .set_max_delta(GIANT_DELTA);
+    app.update();
+
+    let scroll = app.world().resource::<FlappConfig>().scroll_speed * GIANT_DELTA.as_secs_f32();
+    let world = app.world_mut();
+    let mut pairs = world.query_filtered::<&mut PhysicsTransform, With<PipePair>>();
+    let mut first = pairs
+        .iter_mut(world)
+        .min_by(|a, b| a.translation.x.total_cmp(&b.translation.x))
+        .unwrap();
+    //the pipe ends up as far behind the bird as it starts ahead, never overlapping at either end
+    first.translation = Vec2::new(scroll / 2., gap_y);
+    world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Playing);
+
+    app.update();
+    app.update();
+    *app.world().resource::<State<GameState>>().get()
+}
+
+#[test]
+fn giant_tick_through_a_pipe_still_kills() {
+    for hitbox in HITBOXES {
+        assert_eq!(
+            run_giant_tick(hitbox, 400.),
+            GameState::GameOver,
+            "{hitbox:?}"
+        );
+    }
+}
+
+#[test]
+fn giant_tick_through_the_gap_survives() {
+    for hitbox in HITBOXES {
+        assert_eq!(run_giant_tick(hitbox, 0.), GameState::Playing, "{hitbox:?}");
+    }
+}
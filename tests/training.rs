@@ -0,0 +1,76 @@
+mod common;
+
+use std::{env, fs, path::PathBuf, process};
+
+use bevy::prelude::*;
+use common::headless_app;
+use flapp::{
+    cli::Args,
+    config::FlappConfig,
+    training::{GenomeOutput, Training, TrainingPlugin, MIN_POPULATION},
+};
+
+#[test]
+fn evolving_keeps_the_fittest() {
+    let mut training = Training::new(10, None, 7);
+    let fitness: Vec<f32> = (0..10).map(|i| i as f32).collect();
+    let fittest = training.genomes[9].clone();
+    training.evolve(&fitness);
+    assert_eq!(training.generation, 2);
+    assert_eq!(training.genomes.len(), 10);
+    assert_eq!(training.genomes[0], fittest);
+}
+
+#[test]
+fn same_seed_evolves_the_same_way() {
+    let evolve = || {
+        let mut training = Training::new(10, None, 7);
+        for _ in 0..3 {
+            let fitness: Vec<f32> = training.genomes.iter().map(|g| g.weights[0]).collect();
+            training.evolve(&fitness);
+        }
+        training.genomes
+    };
+    assert_eq!(evolve(), evolve());
+}
+
+#[test]
+fn populations_too_small_to_breed_are_grown() {
+    let mut training = Training::new(0, None, 7);
+    assert_eq!(training.genomes.len(), MIN_POPULATION);
+    training.evolve(&[0., 1.]);
+    assert_eq!(training.genomes.len(), MIN_POPULATION);
+}
+
+//a headless training run at `ticks_per_frame` physics ticks per update, stopping after `generations`
+fn train(generations: u32, ticks_per_frame: u32, output: PathBuf) -> App {
+    let args = Args {
+        seed: Some(1),
+        headless: true,
+        train: true,
+        ..Default::default()
+    };
+    let mut app = headless_app(FlappConfig::default(), args, ticks_per_frame);
+    app.insert_resource(Training::new(10, Some(generations), 1))
+        .insert_resource(GenomeOutput(output))
+        .add_plugins(TrainingPlugin);
+    app
+}
+
+#[test]
+fn generations_end_once() {
+    let path = env::temp_dir().join(format!("flapp-genome-{}.ron", process::id()));
+    let mut app = train(3, 8, path.clone());
+    let mut generation = 1;
+    while app.should_exit().is_none() {
+        app.update();
+        let next = app.world().resource::<Training>().generation;
+        assert!(next == generation || next == generation + 1);
+        generation = next;
+    }
+    assert_eq!(generation, 3);
+    assert_eq!(app.world().resource::<Events<AppExit>>().len(), 1);
+    let saved = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert!(!saved.is_empty());
+}